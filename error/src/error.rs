@@ -1,26 +1,92 @@
 use std::fmt::{Display, Formatter};
 
 use actix_web::{HttpResponse, ResponseError};
-use diesel::result::Error as DieselError;
+use actix_web::http::StatusCode;
+use actix_web::http::header::{ETag, EntityTag};
+use diesel::r2d2::PoolError;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use juniper::{ScalarValue, FieldError, IntoFieldError, graphql_value};
 use serde::{Deserialize, Serialize};
-use validator::{ValidationErrors, ValidationErrorsKind};
+use juniper::{Object, Value};
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug)]
 pub enum ServerErrorResponse {
     InternalServerError(Vec<ErrorCode>),
     BadReq(Vec<ErrorCode>),
     NotFound(ErrorCode),
+    Unauthorized(ErrorCode),
+    Forbidden(ErrorCode),
+    Conflict(Vec<ErrorCode>),
+    Unprocessable(Vec<ErrorCode>),
+    ServiceUnavailable(ErrorCode),
+    // The current version of the record that has been changed by another request.
+    VersionConflict(i32),
 }
 
 impl ResponseError for ServerErrorResponse {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ServerErrorResponse::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServerErrorResponse::BadReq(_) => StatusCode::BAD_REQUEST,
+            ServerErrorResponse::NotFound(_) => StatusCode::NOT_FOUND,
+            ServerErrorResponse::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ServerErrorResponse::Forbidden(_) => StatusCode::FORBIDDEN,
+            ServerErrorResponse::Conflict(_) => StatusCode::CONFLICT,
+            ServerErrorResponse::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ServerErrorResponse::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ServerErrorResponse::VersionConflict(_) => StatusCode::CONFLICT,
+        }
+    }
+
     fn error_response(&self) -> HttpResponse {
+        self.problem_response(None)
+    }
+}
+
+impl ServerErrorResponse {
+    pub fn error_codes(&self) -> Vec<ErrorCode> {
         match self {
-            ServerErrorResponse::InternalServerError(errors) => HttpResponse::InternalServerError().json(errors),
-            ServerErrorResponse::BadReq(errors) => HttpResponse::BadRequest().json(errors),
-            ServerErrorResponse::NotFound(errors) => HttpResponse::NotFound().json(errors),
+            ServerErrorResponse::InternalServerError(errors) => errors.clone(),
+            ServerErrorResponse::BadReq(errors) => errors.clone(),
+            ServerErrorResponse::NotFound(error) => vec![error.clone()],
+            ServerErrorResponse::Unauthorized(error) => vec![error.clone()],
+            ServerErrorResponse::Forbidden(error) => vec![error.clone()],
+            ServerErrorResponse::Conflict(errors) => errors.clone(),
+            ServerErrorResponse::Unprocessable(errors) => errors.clone(),
+            ServerErrorResponse::ServiceUnavailable(error) => vec![error.clone()],
+            ServerErrorResponse::VersionConflict(_) => vec![ErrorCode::new("version-conflict")],
+        }
+    }
+
+    // The instance is the path of the request, it is only known by the middleware that wraps the handlers.
+    pub fn problem_details(&self, instance: Option<&str>) -> ProblemDetails {
+        let status = self.status_code();
+        let errors = self.error_codes();
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Unknown Error").to_string(),
+            status: status.as_u16(),
+            detail: errors.iter().map(|error| error.code.as_str()).collect::<Vec<_>>().join(", "),
+            instance: instance.map(|instance| instance.to_string()),
+            errors,
+            current_version: match self {
+                ServerErrorResponse::VersionConflict(current_version) => Some(*current_version),
+                _ => None,
+            },
         }
     }
+
+    pub fn problem_response(&self, instance: Option<&str>) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        // The conflicted client can retry its update with the current version as the If-Match header.
+        if let ServerErrorResponse::VersionConflict(current_version) = self {
+            response.insert_header(ETag(EntityTag::new_strong(current_version.to_string())));
+        }
+        response
+            .content_type("application/problem+json")
+            .json(self.problem_details(instance))
+    }
 }
 
 #[derive(Debug)]
@@ -30,32 +96,66 @@ pub enum Error {
     InternalServerError(String),
     NotFound(String),
     HttpRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    ServiceUnavailable(String),
     DuplicationError,
     DeletedDuplicationError,
+    ValidationError(Vec<ErrorCode>),
+    // The expected version of the update does not match the current version of the record.
+    VersionConflict(i32),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorCode {
     pub code: String,
+    // The name of the invalid field of the payload, it is set for the validation errors only.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub field: Option<String>,
 }
 
 impl ErrorCode {
+    pub fn new(code: &str) -> Self {
+        Self { code: code.to_string(), field: None }
+    }
+
     pub fn validate_errors(error: ValidationErrors, errors: &mut Vec<ErrorCode>) {
-        for value in error.errors().values() {
+        Self::collect_validation_errors(&error, None, errors);
+    }
+
+    // The errors of a list payload are prefixed by the index of the invalid item like "[1].email".
+    pub fn validate_list_errors<T: Validate>(items: &[T], errors: &mut Vec<ErrorCode>) {
+        for (index, item) in items.iter().enumerate() {
+            if let Err(error) = item.validate() {
+                Self::collect_validation_errors(&error, Some(&format!("[{}]", index)), errors);
+            }
+        }
+    }
+
+    // The nested structs and lists are flattened into paths like "members[0].name".
+    fn collect_validation_errors(error: &ValidationErrors, parent_field: Option<&str>, errors: &mut Vec<ErrorCode>) {
+        let mut fields: Vec<_> = error.errors().iter().collect();
+        fields.sort_by_key(|(field, _)| *field);
+        for (field, value) in fields {
+            let field_path = match parent_field {
+                Some(parent_field) => format!("{}.{}", parent_field, field),
+                None => field.to_string(),
+            };
             match value {
-                ValidationErrorsKind::Struct(_) => {}
-                ValidationErrorsKind::List(_) => {}
+                ValidationErrorsKind::Struct(nested_error) => {
+                    Self::collect_validation_errors(nested_error, Some(&field_path), errors)
+                }
+                ValidationErrorsKind::List(nested_errors) => {
+                    for (index, nested_error) in nested_errors {
+                        Self::collect_validation_errors(nested_error, Some(&format!("{}[{}]", field_path, index)), errors)
+                    }
+                }
                 ValidationErrorsKind::Field(validation_error_vec) => {
                     for validation_error in validation_error_vec {
-                        let code = validation_error.clone().code.to_string();
-                        let _message = validation_error
-                            .clone()
-                            .message
-                            .expect("Validation Error")
-                            .to_string();
                         errors.push(ErrorCode {
-                            code,
+                            code: validation_error.code.to_string(),
+                            field: Some(field_path.clone()),
                         });
                     }
                 }
@@ -64,6 +164,21 @@ impl ErrorCode {
     }
 }
 
+/// The RFC 7807 problem details document returned by the REST endpoints, the error codes are in the "errors" extension.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub errors: Vec<ErrorCode>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub current_version: Option<i32>,
+}
+
 pub struct ErrorCodesWrapper {
     error_codes: Vec<ErrorCode>,
 }
@@ -77,7 +192,58 @@ impl ErrorCodesWrapper {
 
 impl From<DieselError> for Error {
     fn from(err: DieselError) -> Self {
-        Self::DBError(err)
+        match err {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => Self::DuplicationError,
+            err => Self::DBError(err),
+        }
+    }
+}
+
+// The pool returns an error when no connection is available before the connection timeout.
+impl From<PoolError> for Error {
+    fn from(_: PoolError) -> Self {
+        Self::ServiceUnavailable("database-unavailable".to_string())
+    }
+}
+
+impl From<ValidationErrors> for Error {
+    fn from(err: ValidationErrors) -> Self {
+        let mut error_codes = Vec::new();
+        ErrorCode::validate_errors(err, &mut error_codes);
+        Self::ValidationError(error_codes)
+    }
+}
+
+impl Error {
+    // Run the validator derives of every item of a list payload and collect all the errors at once.
+    pub fn validate_list<T: Validate>(items: &[T]) -> Result<(), Error> {
+        let mut error_codes = Vec::new();
+        ErrorCode::validate_list_errors(items, &mut error_codes);
+        match error_codes.is_empty() {
+            true => Ok(()),
+            false => Err(Self::ValidationError(error_codes)),
+        }
+    }
+
+    // The HTTP status that describes the error for the REST clients.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Error::DBError(DieselError::NotFound) => StatusCode::NOT_FOUND,
+            Error::DBError(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => StatusCode::CONFLICT,
+            Error::DBError(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::DBError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Error::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::HttpRequest(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Error::DuplicationError => StatusCode::CONFLICT,
+            Error::DeletedDuplicationError => StatusCode::CONFLICT,
+            Error::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::VersionConflict(_) => StatusCode::CONFLICT,
+        }
     }
 }
 
@@ -101,15 +267,20 @@ impl From<Error> for ErrorCodesWrapper {
             Error::InternalServerError(error) => Self::from(error.as_str()),
             Error::NotFound(error) => Self::from(error.as_str()),
             Error::HttpRequest(error) => Self::from(error.as_str()),
+            Error::Unauthorized(error) => Self::from(error.as_str()),
+            Error::Forbidden(error) => Self::from(error.as_str()),
+            Error::ServiceUnavailable(error) => Self::from(error.as_str()),
             Error::DuplicationError => Self::from("duplication-error"),
             Error::DeletedDuplicationError => Self::from("deleted-duplication-error"),
+            Error::ValidationError(error_codes) => Self { error_codes },
+            Error::VersionConflict(_) => Self::from("version-conflict"),
         }
     }
 }
 
 impl From<&str> for ErrorCodesWrapper {
     fn from(str: &str) -> Self {
-        Self { error_codes: vec![ErrorCode { code: str.to_string() }] }
+        Self { error_codes: vec![ErrorCode::new(str)] }
     }
 }
 
@@ -119,6 +290,26 @@ impl From<Vec<ErrorCode>> for ServerErrorResponse {
     }
 }
 
+impl From<Error> for ServerErrorResponse {
+    fn from(err: Error) -> Self {
+        if let Error::VersionConflict(current_version) = err {
+            return Self::VersionConflict(current_version);
+        }
+        let status_code = err.status_code();
+        let error_codes = ErrorCodesWrapper::from(err).get_error_codes();
+        match status_code {
+            StatusCode::BAD_REQUEST => Self::BadReq(error_codes),
+            StatusCode::NOT_FOUND => Self::NotFound(error_codes[0].clone()),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(error_codes[0].clone()),
+            StatusCode::FORBIDDEN => Self::Forbidden(error_codes[0].clone()),
+            StatusCode::CONFLICT => Self::Conflict(error_codes),
+            StatusCode::UNPROCESSABLE_ENTITY => Self::Unprocessable(error_codes),
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable(error_codes[0].clone()),
+            _ => Self::InternalServerError(error_codes),
+        }
+    }
+}
+
 
 impl Display for ServerErrorResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -126,6 +317,12 @@ impl Display for ServerErrorResponse {
             ServerErrorResponse::InternalServerError(_) => write!(f, "Internal Server Error Display."),
             ServerErrorResponse::BadReq(_) => write!(f, "Bas Request Display."),
             ServerErrorResponse::NotFound(_) => write!(f, "Not Found Display."),
+            ServerErrorResponse::Unauthorized(_) => write!(f, "Unauthorized Display."),
+            ServerErrorResponse::Forbidden(_) => write!(f, "Forbidden Display."),
+            ServerErrorResponse::Conflict(_) => write!(f, "Conflict Display."),
+            ServerErrorResponse::Unprocessable(_) => write!(f, "Unprocessable Display."),
+            ServerErrorResponse::ServiceUnavailable(_) => write!(f, "Service Unavailable Display."),
+            ServerErrorResponse::VersionConflict(_) => write!(f, "Version Conflict Display."),
         }
     }
 }
@@ -150,8 +347,164 @@ impl<S: ScalarValue> IntoFieldError<S> for Error {
             Error::InternalServerError(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "InternalServerError" })),
             Error::NotFound(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "NotFound" })),
             Error::HttpRequest(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "HttpRequest" })),
+            Error::Unauthorized(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "Unauthorized" })),
+            Error::Forbidden(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "Forbidden" })),
+            Error::ServiceUnavailable(_) => FieldError::new("gql_bad_request", graphql_value!({ "type": "ServiceUnavailable" })),
             Error::DuplicationError => FieldError::new("gql_bad_request", graphql_value!({ "type": "DuplicationError" })),
             Error::DeletedDuplicationError => FieldError::new("gql_bad_request", graphql_value!({ "type": "DeletedDuplicationError" })),
+            Error::ValidationError(error_codes) => FieldError::new("gql_validation_error", validation_extensions(&error_codes)),
+            Error::VersionConflict(current_version) => {
+                let mut extensions = Object::with_capacity(2);
+                extensions.add_field("type", Value::scalar("VersionConflict".to_string()));
+                extensions.add_field("currentVersion", Value::scalar(current_version));
+                FieldError::new("gql_version_conflict", Value::object(extensions))
+            }
+        }
+    }
+}
+
+// The extensions of the GraphQL validation error carry the same codes and fields as the REST response.
+fn validation_extensions<S: ScalarValue>(error_codes: &[ErrorCode]) -> Value<S> {
+    let errors = error_codes
+        .iter()
+        .map(|error_code| {
+            let mut error = Object::with_capacity(2);
+            error.add_field("code", Value::scalar(error_code.code.clone()));
+            if let Some(field) = &error_code.field {
+                error.add_field("field", Value::scalar(field.clone()));
+            }
+            Value::object(error)
+        })
+        .collect();
+    let mut extensions = Object::with_capacity(2);
+    extensions.add_field("type", Value::scalar("ValidationError".to_string()));
+    extensions.add_field("errors", Value::list(errors));
+    Value::object(extensions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database_error(kind: DatabaseErrorKind) -> DieselError {
+        DieselError::DatabaseError(kind, Box::new("database error".to_string()))
+    }
+
+    fn response_status(err: Error) -> StatusCode {
+        ServerErrorResponse::from(err).error_response().status()
+    }
+
+    #[test]
+    fn diesel_not_found_is_404() {
+        assert_eq!(response_status(Error::from(DieselError::NotFound)), StatusCode::NOT_FOUND);
+        assert_eq!(response_status(Error::NotFound("team-not-found".to_string())), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn unique_violation_is_409() {
+        let err = Error::from(database_error(DatabaseErrorKind::UniqueViolation));
+        assert!(matches!(err, Error::DuplicationError));
+        assert_eq!(response_status(err), StatusCode::CONFLICT);
+        assert_eq!(response_status(Error::DBError(database_error(DatabaseErrorKind::UniqueViolation))), StatusCode::CONFLICT);
+        assert_eq!(response_status(Error::DeletedDuplicationError), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn foreign_key_violation_is_422() {
+        let err = Error::from(database_error(DatabaseErrorKind::ForeignKeyViolation));
+        assert_eq!(response_status(err), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn request_errors_are_4xx() {
+        assert_eq!(response_status(Error::BadRequest("bad-request".to_string())), StatusCode::BAD_REQUEST);
+        assert_eq!(response_status(Error::Unauthorized("invalid-access-token".to_string())), StatusCode::UNAUTHORIZED);
+        assert_eq!(response_status(Error::Forbidden("permission-denied".to_string())), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn other_errors_are_500() {
+        assert_eq!(response_status(Error::from(DieselError::RollbackTransaction)), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response_status(Error::InternalServerError("password-hash-error".to_string())), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response_status(Error::HttpRequest("http-error".to_string())), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn unavailable_database_is_503() {
+        let err = Error::ServiceUnavailable("database-unavailable".to_string());
+        assert_eq!(response_status(err), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn error_codes_are_kept() {
+        match ServerErrorResponse::from(Error::from(DieselError::NotFound)) {
+            ServerErrorResponse::NotFound(error_code) => assert_eq!(error_code.code, "object-not-found"),
+            other => panic!("Unexpected response {:?}", other),
+        }
+        match ServerErrorResponse::from(Error::DuplicationError) {
+            ServerErrorResponse::Conflict(error_codes) => assert_eq!(error_codes[0].code, "duplication-error"),
+            other => panic!("Unexpected response {:?}", other),
+        }
+    }
+
+    #[test]
+    fn problem_details_document() {
+        let problem_details = ServerErrorResponse::from(Error::DuplicationError).problem_details(Some("/team/insert"));
+        assert_eq!(problem_details.problem_type, "about:blank");
+        assert_eq!(problem_details.title, "Conflict");
+        assert_eq!(problem_details.status, 409);
+        assert_eq!(problem_details.detail, "duplication-error");
+        assert_eq!(problem_details.instance, Some("/team/insert".to_string()));
+        assert_eq!(problem_details.errors[0].code, "duplication-error");
+    }
+
+    #[test]
+    fn problem_response_content_type() {
+        let response = ServerErrorResponse::NotFound(ErrorCode::new("object-not-found")).error_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+    }
+
+    #[test]
+    fn version_conflict_is_409_with_the_current_version() {
+        let response = ServerErrorResponse::from(Error::VersionConflict(3));
+        assert_eq!(response.problem_details(None).current_version, Some(3));
+        assert_eq!(response.problem_details(None).errors[0].code, "version-conflict");
+        let response = response.error_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers().get("etag").unwrap(), "\"3\"");
+    }
+
+    #[test]
+    fn validation_errors_keep_the_field() {
+        let mut validation_errors = ValidationErrors::new();
+        validation_errors.add("email", validator::ValidationError::new("email-format-error"));
+        let mut errors = Vec::new();
+        ErrorCode::validate_errors(validation_errors, &mut errors);
+        assert_eq!(errors[0].code, "email-format-error");
+        assert_eq!(errors[0].field, Some("email".to_string()));
+    }
+
+    #[test]
+    fn validation_error_is_422_with_the_indexed_fields() {
+        #[derive(Validate)]
+        struct Payload {
+            #[validate(email(code = "email-format-error"))]
+            email: String,
+        }
+
+        let payload = vec![
+            Payload { email: "a@b.com".to_string() },
+            Payload { email: "invalid".to_string() },
+        ];
+        let err = Error::validate_list(&payload).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        match ServerErrorResponse::from(err) {
+            ServerErrorResponse::Unprocessable(error_codes) => {
+                assert_eq!(error_codes[0].code, "email-format-error");
+                assert_eq!(error_codes[0].field, Some("[1].email".to_string()));
+            }
+            other => panic!("Unexpected response {:?}", other),
         }
     }
-}
\ No newline at end of file
+}
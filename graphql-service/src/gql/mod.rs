@@ -1,39 +1,51 @@
-use std::env;
+use std::{env, fs, io};
 
-use actix_web::{Error, HttpResponse, web};
+use actix_web::{Error, HttpRequest, HttpResponse, web};
+use actix_web::http::header::AUTHORIZATION;
 use actix_web::web::Data;
+use juniper::http::graphiql::graphiql_source;
 use juniper::http::GraphQLRequest;
 
-use yugabyte::context::GraphQLContext;
-use yugabyte::db_connection::PgPool;
+use yugabyte::context::{GraphQLContext, RelationLoader};
+use yugabyte::db_connection::{AuthDBPool, CoreDBPool};
+use yugabyte::util::jwt::{Claims, decode_access_token};
 
-use crate::gql::schema::auth_user_schema::{auth_user_schema, AuthUserSchema};
-use crate::gql::schema::member_schema::{member_schema, MemberSchema};
+use crate::gql::schema::{schema, Schema, schema_language};
 
 mod schema;
 
 pub fn routes(config: &mut web::ServiceConfig) {
-    let auth_schema = Data::new(auth_user_schema());
-    let member_schema = Data::new(member_schema());
+    let schema = Data::new(schema());
     config
-        .app_data(auth_schema)
-        .app_data(member_schema)
-        .route("/graphql", web::post().to(auth_user_graphql))
-        .route("/graphql", web::post().to(member_graphql));
+        .app_data(schema)
+        .route("/graphql", web::post().to(graphql))
+        .route("/graphql/schema.graphql", web::get().to(graphql_schema))
+        .route("/graphiql", web::get().to(graphiql));
+}
+
+// Write the SDL of the schema for the client codegen, it is the same file as the one served on /graphql/schema.graphql.
+pub fn write_schema(schema_path: &str) -> io::Result<()> {
+    fs::write(schema_path, schema_language(&schema()))
 }
 
 // The core handler that provides all GraphQL functionality.
-async fn auth_user_graphql(
-    // The DB connection pool
-    pool: web::Data<PgPool>,
+async fn graphql(
+    // The DB connection pools
+    pool: web::Data<CoreDBPool>,
+    auth_pool: web::Data<AuthDBPool>,
     // The GraphQL schema
-    schema: web::Data<AuthUserSchema>,
+    schema: web::Data<Schema>,
     // The incoming HTTP request
+    req: HttpRequest,
     data: web::Json<GraphQLRequest>,
 ) -> Result<HttpResponse, Error> {
     // Instantiate a context
     let context = GraphQLContext {
-        pool: pool.get_ref().to_owned(),
+        pool: pool.0.to_owned(),
+        read_pools: pool.1.to_owned(),
+        auth_pool: auth_pool.0.to_owned(),
+        claims: bearer_claims(&req),
+        loader: RelationLoader::default(),
     };
 
     // Handle the incoming request and return a string result (or error)
@@ -50,34 +62,251 @@ async fn auth_user_graphql(
         .body(res))
 }
 
-async fn member_graphql(
-    // The DB connection pool
-    pool: web::Data<PgPool>,
-    // The GraphQL schema
-    schema: web::Data<MemberSchema>,
-    // The incoming HTTP request
-    data: web::Json<GraphQLRequest>,
-) -> Result<HttpResponse, Error> {
-    // Instantiate a context
-    let context = GraphQLContext {
-        pool: pool.get_ref().to_owned(),
-    };
+// The GraphiQL IDE to explore the schema interactively, it sends its queries to /graphql.
+async fn graphiql() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(graphiql_source("/graphql", None))
+}
 
-    // Handle the incoming request and return a string result (or error)
-    let res = web::block(move || {
-        let res = data.execute_sync(&schema, &context);
-        Ok::<_, serde_json::error::Error>(serde_json::to_string(&res)?)
-    })
-        .await
-        .map_err(Error::from)??;
+async fn graphql_schema(schema: web::Data<Schema>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(schema_language(&schema))
+}
 
-    // Return the string as a JSON payload
-    Ok(HttpResponse::Ok()
-        .content_type("application/json")
-        .body(res))
+// Decode the access token of the "Authorization: Bearer" header, the resolvers that need it will reject the request without it.
+fn bearer_claims(req: &HttpRequest) -> Option<Claims> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|header_value| header_value.to_str().ok())
+        .and_then(|header_value| header_value.strip_prefix("Bearer "))
+        .and_then(|token| decode_access_token(token.trim()).ok())
 }
 
 pub(crate) fn logging_setup() {
     env::set_var("RUST_LOG", "actix_web=info");
     env_logger::init();
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, test};
+    use chrono::Utc;
+    use diesel::{Connection, PgConnection, RunQueryDsl};
+    use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use yugabyte::model::auth_user::AuthUser;
+    use yugabyte::model::member::Member;
+    use yugabyte::model::role::Role;
+    use yugabyte::model::team::Team;
+    use yugabyte::model::user::User;
+    use yugabyte::schema::{member, team, user};
+    use yugabyte::util::jwt::encode_access_token;
+
+    use super::*;
+
+    const QUERIES: [&str; 11] = [
+        "allUsers", "findAuthUser",
+        "listMembers", "findMemberById", "filterMembersByTheName", "retrieveAllMemberNamesByTeamId",
+        "listTeams", "findTeam",
+        "listUsers", "findUser",
+        "search",
+    ];
+    const MUTATIONS: [&str; 15] = [
+        "registerUser",
+        "createMember", "createBulkMembers", "updateOneMember", "restoreMember",
+        "createTeam", "createBulkTeams", "updateTeam", "removeTeam", "restoreTeam",
+        "createUser", "createBulkUsers", "updateUser", "removeUser", "restoreUser",
+    ];
+
+    // The introspection doesn't touch the databases, so the pools are never connected.
+    fn unconnected_pool() -> Pool<ConnectionManager<diesel::PgConnection>> {
+        Pool::builder().build_unchecked(ConnectionManager::new("postgres://localhost/unconnected"))
+    }
+
+    // The only connection of the pool runs in a transaction that is never committed, so the records of the test are not kept.
+    #[derive(Debug)]
+    struct TestTransaction;
+
+    impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for TestTransaction {
+        fn on_acquire(&self, connection: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+            connection.begin_test_transaction().map_err(diesel::r2d2::Error::QueryError)
+        }
+    }
+
+    fn test_transaction_pool() -> Pool<ConnectionManager<PgConnection>> {
+        dotenv::dotenv().expect("Failed to read .env file");
+        let url = env::var("DATABASE_URL").unwrap();
+        Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(TestTransaction))
+            .build(ConnectionManager::new(url))
+            .expect("Failed to connect to the core database")
+    }
+
+    async fn call_graphql_service(req: test::TestRequest) -> actix_web::dev::ServiceResponse {
+        call_graphql_service_with(unconnected_pool(), req).await
+    }
+
+    async fn call_graphql_service_with(
+        pool: Pool<ConnectionManager<PgConnection>>,
+        req: test::TestRequest,
+    ) -> actix_web::dev::ServiceResponse {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(CoreDBPool(pool, Vec::new())))
+                .app_data(Data::new(AuthDBPool(unconnected_pool())))
+                .configure(routes)
+        ).await;
+        test::call_service(&app, req.to_request()).await
+    }
+
+    fn field_names(response: &Value, root_type: &str) -> Vec<String> {
+        let mut names: Vec<String> = response["data"]["__schema"][root_type]["fields"]
+            .as_array()
+            .expect("The root type has no fields")
+            .iter()
+            .map(|field| field["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[actix_web::test]
+    async fn every_resolver_is_reachable_through_graphql() {
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(json!({ "query": "{ __schema { queryType { fields { name } } mutationType { fields { name } } } }" }));
+        let response: Value = test::read_body_json(call_graphql_service(req).await).await;
+
+        assert_eq!(field_names(&response, "queryType"), sorted(&QUERIES));
+        assert_eq!(field_names(&response, "mutationType"), sorted(&MUTATIONS));
+    }
+
+    // The type of the error returned for the only field of the query.
+    async fn error_type(query: &str) -> String {
+        let req = test::TestRequest::post().uri("/graphql").set_json(json!({ "query": query }));
+        let response: Value = test::read_body_json(call_graphql_service(req).await).await;
+        response["errors"][0]["extensions"]["type"].as_str().unwrap_or_default().to_string()
+    }
+
+    // The claims are checked before connecting to the databases, so the fields are rejected as unauthorized.
+    #[actix_web::test]
+    async fn the_resolvers_require_an_access_token() {
+        const QUERIES_WITHOUT_TOKEN: [&str; 15] = [
+            "{ allUsers(paginationDto: { pageSize: 10 }) { nextCursor } }",
+            r#"{ findAuthUser(authUserId: "00000000-0000-0000-0000-000000000000") { id } }"#,
+            "{ listUsers(paginationDto: { pageSize: 10 }) { nextCursor } }",
+            r#"{ findUser(userId: "00000000-0000-0000-0000-000000000000") { id } }"#,
+            r#"mutation { createUser(newUser: { email: "a@b.com", name: "a", password: "123456789" }) { user { id } } }"#,
+            r#"mutation { createBulkUsers(newUsers: []) { user { id } } }"#,
+            "{ listTeams(paginationDto: { pageSize: 10 }) { nextCursor } }",
+            r#"{ findTeam(teamId: "00000000-0000-0000-0000-000000000000") { id } }"#,
+            r#"mutation { createTeam(newTeam: { name: "team", description: "" }) { id } }"#,
+            r#"mutation { createBulkTeams(newTeams: []) { id } }"#,
+            "{ listMembers(paginationDto: { pageSize: 10 }) { nextCursor } }",
+            r#"{ findMemberById(authUserId: "00000000-0000-0000-0000-000000000000") { id } }"#,
+            r#"{ filterMembersByTheName(memberName: "member") { id } }"#,
+            r#"{ retrieveAllMemberNamesByTeamId(teamId: "00000000-0000-0000-0000-000000000000") { name } }"#,
+            r#"{ search(searchDto: { q: "member" }) { __typename } }"#,
+        ];
+        for query in QUERIES_WITHOUT_TOKEN {
+            assert_eq!(error_type(query).await, "Unauthorized", "{}", query);
+        }
+    }
+
+    // The secret of the .env file is empty, the tests sign their tokens with their own one.
+    fn access_token(user: &User) -> String {
+        if env::var("JWT_SECRET").map_or(true, |secret| secret.is_empty()) {
+            env::set_var("JWT_SECRET", "a-secret-of-the-graphql-tests-of-32-characters");
+        }
+        let auth_user = AuthUser { id: user.id, email: user.email.clone(), password: String::new() };
+        encode_access_token(&auth_user).unwrap()
+    }
+
+    fn new_team(name: &str) -> Team {
+        Team { id: Uuid::new_v4(), name: name.to_string(), description: String::new(), deleted_at: None, version: 1 }
+    }
+
+    fn new_member(team_id: Uuid, user_id: Uuid, name: &str) -> Member {
+        Member {
+            id: Uuid::new_v4(),
+            team_id,
+            user_id,
+            name: name.to_string(),
+            identity_num: user_id.to_string(),
+            role: Role::Member,
+            assigned_at: Utc::now().naive_utc(),
+            expired_at: None,
+            modification_date: None,
+            deleted_at: None,
+            version: 1,
+        }
+    }
+
+    #[actix_web::test]
+    async fn the_relationships_of_the_members_are_resolved() {
+        let pool = test_transaction_pool();
+        let first_team = new_team("first");
+        let second_team = new_team("second");
+        let member_user = User {
+            id: Uuid::new_v4(),
+            email: format!("{}@b.com", Uuid::new_v4()),
+            name: "user".to_string(),
+            deleted_at: None,
+        };
+        let first_member = new_member(first_team.id, member_user.id, "first member");
+        let second_member = new_member(second_team.id, member_user.id, "second member");
+        {
+            let pg_connection = pool.get().unwrap();
+            let pg_connection: &PgConnection = &pg_connection;
+            diesel::insert_into(team::table).values(&vec![first_team, second_team]).execute(pg_connection).unwrap();
+            diesel::insert_into(user::table).values(&member_user).execute(pg_connection).unwrap();
+            diesel::insert_into(member::table).values(&vec![first_member.clone(), second_member]).execute(pg_connection).unwrap();
+        }
+
+        let query = format!(
+            r#"{{ findMemberById(authUserId: "{}") {{
+                team {{ name members(paginationDto: {{ pageSize: 10 }}) {{ paginatedList {{ name }} }} }}
+                user {{ email memberships {{ name team {{ name }} user {{ name }} }} }}
+            }} }}"#,
+            first_member.id,
+        );
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .insert_header((AUTHORIZATION, format!("Bearer {}", access_token(&member_user))))
+            .set_json(json!({ "query": query }));
+        let response: Value = test::read_body_json(call_graphql_service_with(pool, req).await).await;
+
+        assert_eq!(response["errors"], Value::Null, "{}", response);
+        let found_member = &response["data"]["findMemberById"];
+        assert_eq!(found_member["team"]["name"], "first");
+        assert_eq!(found_member["team"]["members"]["paginatedList"], json!([{ "name": "first member" }]));
+        assert_eq!(found_member["user"]["email"], member_user.email.as_str());
+        let mut memberships: Vec<Value> = found_member["user"]["memberships"].as_array().unwrap().clone();
+        memberships.sort_by_key(|membership| membership["name"].as_str().unwrap().to_string());
+        assert_eq!(memberships, vec![
+            json!({ "name": "first member", "team": { "name": "first" }, "user": { "name": "user" } }),
+            json!({ "name": "second member", "team": { "name": "second" }, "user": { "name": "user" } }),
+        ]);
+    }
+
+    #[actix_web::test]
+    async fn graphiql_and_the_sdl_are_served() {
+        let response = call_graphql_service(test::TestRequest::get().uri("/graphiql")).await;
+        assert!(response.status().is_success());
+
+        let response = call_graphql_service(test::TestRequest::get().uri("/graphql/schema.graphql")).await;
+        assert!(response.status().is_success());
+        assert_eq!(test::read_body(response).await, schema_language(&schema()));
+    }
+}
@@ -0,0 +1,42 @@
+use diesel::pg::PgConnection;
+use uuid::Uuid;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::auth_user::{find_auth_user_by_id, list_all_auth_users};
+use yugabyte::model::auth_user::AuthUserView;
+use yugabyte::model::dto::{AuthUserPage, PaginationDTO};
+use yugabyte::model::user::NewUser;
+
+use crate::gql::schema::validate_input;
+use crate::gql::schema::object::RegisteredUserObject;
+
+// The auth_user resolvers use the pool of the auth database.
+pub(crate) fn list_auth_users(pagination_dto: PaginationDTO, context: &GraphQLContext) -> Result<AuthUserPage, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    list_all_auth_users(&pagination_dto, pg_connection).map(|page| AuthUserPage {
+        paginated_list: page.items.into_iter().map(AuthUserView::from).collect(),
+        next_cursor: page.next_cursor,
+    })
+}
+
+pub(crate) fn find_auth_user(auth_user_id: Uuid, context: &GraphQLContext) -> Result<AuthUserView, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    find_auth_user_by_id(&auth_user_id, pg_connection).map(AuthUserView::from)
+}
+
+// Create the User and its AuthUser with the same id.
+pub(crate) fn register_user(
+    context: &GraphQLContext,
+    new_user: NewUser,
+) -> Result<RegisteredUserObject, Error> {
+    validate_input(&new_user)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    let auth_pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    new_user.register_user(pg_connection, auth_pg_connection).map(RegisteredUserObject::from)
+}
@@ -0,0 +1,119 @@
+use diesel::PgConnection;
+use uuid::Uuid;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::authorization::{authorize_member_change, authorize_member_role};
+use yugabyte::engine::member as member_engine;
+use yugabyte::engine::member::{
+    filter_members_by_name, get_all_member_names_by_team_id, insert_bulk_members, list_all_members,
+    restore_member_by_id, update_member,
+};
+use yugabyte::engine::retry::retry_transaction;
+use yugabyte::model::dto::{ListQueryDTO, PaginationDTO};
+use yugabyte::model::member::{Member, Name, NewMember, UpdateMember};
+use yugabyte::util::utils::current_timestamp;
+
+use crate::gql::schema::{validate_input, validate_inputs};
+use crate::gql::schema::object::{MemberObject, MemberPageObject};
+
+pub(crate) fn list_members(
+    pagination_dto: PaginationDTO,
+    list_query_dto: Option<ListQueryDTO>,
+    context: &GraphQLContext,
+) -> Result<MemberPageObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    list_all_members(&pagination_dto, &list_query_dto.unwrap_or_default(), pg_connection)
+        .map(|page| MemberPageObject::new(page, context))
+}
+
+pub(crate) fn find_member_by_id(auth_user_id: Uuid, context: &GraphQLContext) -> Result<MemberObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    member_engine::find_member_by_id(&auth_user_id, pg_connection).map(MemberObject)
+}
+
+pub(crate) fn filter_members_by_the_name(member_name: String, context: &GraphQLContext) -> Result<Vec<MemberObject>, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    filter_members_by_name(&member_name, pg_connection).map(|members| MemberObject::list(members, context))
+}
+
+pub(crate) fn retrieve_all_member_names_by_team_id(
+    team_id: Uuid, context: &GraphQLContext,
+) -> Result<Vec<Name>, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    get_all_member_names_by_team_id(&team_id, pg_connection)
+}
+
+pub(crate) fn create_member(
+    context: &GraphQLContext,
+    new_member: NewMember,
+) -> Result<MemberObject, Error> {
+    validate_input(&new_member)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize_member_role(context.authenticated_claims()?, &new_member.team_id, &new_member.role, pg_connection)?;
+
+    new_member.insert_member(pg_connection).map(MemberObject)
+}
+
+pub(crate) fn create_bulk_members(
+    context: &GraphQLContext,
+    new_members: Vec<NewMember>,
+) -> Result<Vec<MemberObject>, Error> {
+    validate_inputs(&new_members)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+
+    let claims = context.authenticated_claims()?;
+    let mut members = Vec::new();
+
+    // Iterate over the New Members and create the list of members to be added in a bulk not to load the execution time of the database.
+    for new_member in new_members {
+        authorize_member_role(claims, &new_member.team_id, &new_member.role, pg_connection)?;
+        let member = Member {
+            id: Uuid::new_v4(),
+            team_id: new_member.team_id,
+            user_id: new_member.user_id,
+            name: new_member.name,
+            identity_num: new_member.identity_num,
+            role: new_member.role,
+            assigned_at: current_timestamp(),
+            expired_at: new_member.expired_at,
+            modification_date: None,
+            deleted_at: None,
+            version: 1,
+        };
+        members.push(member);
+    }
+
+    insert_bulk_members(&members, pg_connection).map(|members| MemberObject::list(members, context))
+}
+
+pub(crate) fn update_one_member(
+    context: &GraphQLContext,
+    member: UpdateMember,
+) -> Result<MemberObject, Error> {
+    validate_input(&member)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize_member_change(context.authenticated_claims()?, &member.id, Some(&member.role), pg_connection)?;
+
+    update_member(&member, pg_connection).map(MemberObject)
+}
+
+// The restorations are checked after restoring the record, the deleted members are not visible to the authorization.
+pub(crate) fn restore_member(context: &GraphQLContext, member_id: Uuid) -> Result<MemberObject, Error> {
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    let claims = context.authenticated_claims()?;
+
+    retry_transaction(pg_connection, || {
+        let restored_member = restore_member_by_id(&member_id, pg_connection)?;
+        authorize_member_change(claims, &member_id, None, pg_connection)?;
+        Ok(MemberObject(restored_member))
+    })
+}
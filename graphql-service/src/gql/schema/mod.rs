@@ -1,2 +1,203 @@
-pub(crate) mod auth_user_schema;
-pub(crate) mod member_schema;
+use juniper::{EmptySubscription, RootNode};
+use uuid::Uuid;
+use validator::Validate;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::model::auth_user::AuthUserView;
+use yugabyte::model::dto::{AuthUserPage, ListQueryDTO, PaginationDTO};
+use yugabyte::model::member::{Name, NewMember, UpdateMember};
+use yugabyte::model::search::SearchDTO;
+use yugabyte::model::team::{NewTeam, UpdateTeam};
+use yugabyte::model::user::{NewUser, UpdateUser};
+
+use crate::gql::schema::object::{
+    MemberObject, MemberPageObject, RegisteredUserObject, SearchResultObject, TeamObject, TeamPageObject, UserObject,
+    UserPageObject,
+};
+
+pub(crate) mod auth_user;
+pub(crate) mod member;
+pub(crate) mod object;
+pub(crate) mod search;
+pub(crate) mod team;
+pub(crate) mod user;
+
+// Run the validator derives of a mutation input, the errors are returned with the same codes as the REST endpoints.
+pub(crate) fn validate_input<T: Validate>(input: &T) -> Result<(), Error> {
+    input.validate().map_err(Error::from)
+}
+
+pub(crate) fn validate_inputs<T: Validate>(inputs: &[T]) -> Result<(), Error> {
+    Error::validate_list(inputs)
+}
+
+pub struct Query;
+
+// The root Query composes the resolvers of the entity modules, every field delegates to the resolver of the same name.
+#[juniper::graphql_object(context = GraphQLContext)]
+impl Query {
+    #[graphql(name = "allUsers")]
+    pub fn list_auth_users(pagination_dto: PaginationDTO, context: &GraphQLContext) -> Result<AuthUserPage, Error> {
+        auth_user::list_auth_users(pagination_dto, context)
+    }
+
+    pub fn find_auth_user(auth_user_id: Uuid, context: &GraphQLContext) -> Result<AuthUserView, Error> {
+        auth_user::find_auth_user(auth_user_id, context)
+    }
+
+    pub fn list_members(
+        pagination_dto: PaginationDTO,
+        list_query_dto: Option<ListQueryDTO>,
+        context: &GraphQLContext,
+    ) -> Result<MemberPageObject, Error> {
+        member::list_members(pagination_dto, list_query_dto, context)
+    }
+
+    pub fn find_member_by_id(auth_user_id: Uuid, context: &GraphQLContext) -> Result<MemberObject, Error> {
+        member::find_member_by_id(auth_user_id, context)
+    }
+
+    pub fn filter_members_by_the_name(member_name: String, context: &GraphQLContext) -> Result<Vec<MemberObject>, Error> {
+        member::filter_members_by_the_name(member_name, context)
+    }
+
+    pub fn retrieve_all_member_names_by_team_id(team_id: Uuid, context: &GraphQLContext) -> Result<Vec<Name>, Error> {
+        member::retrieve_all_member_names_by_team_id(team_id, context)
+    }
+
+    pub fn list_teams(
+        pagination_dto: PaginationDTO,
+        list_query_dto: Option<ListQueryDTO>,
+        context: &GraphQLContext,
+    ) -> Result<TeamPageObject, Error> {
+        team::list_teams(pagination_dto, list_query_dto, context)
+    }
+
+    pub fn find_team(team_id: Uuid, context: &GraphQLContext) -> Result<TeamObject, Error> {
+        team::find_team(team_id, context)
+    }
+
+    pub fn list_users(
+        pagination_dto: PaginationDTO,
+        list_query_dto: Option<ListQueryDTO>,
+        context: &GraphQLContext,
+    ) -> Result<UserPageObject, Error> {
+        user::list_users(pagination_dto, list_query_dto, context)
+    }
+
+    pub fn find_user(user_id: Uuid, context: &GraphQLContext) -> Result<UserObject, Error> {
+        user::find_user(user_id, context)
+    }
+
+    pub fn search(search_dto: SearchDTO, context: &GraphQLContext) -> Result<Vec<SearchResultObject>, Error> {
+        search::search(search_dto, context)
+    }
+}
+
+
+pub struct Mutation;
+
+#[juniper::graphql_object(context = GraphQLContext)]
+impl Mutation {
+    pub fn register_user(context: &GraphQLContext, new_user: NewUser) -> Result<RegisteredUserObject, Error> {
+        auth_user::register_user(context, new_user)
+    }
+
+    pub fn create_member(context: &GraphQLContext, new_member: NewMember) -> Result<MemberObject, Error> {
+        member::create_member(context, new_member)
+    }
+
+    pub fn create_bulk_members(context: &GraphQLContext, new_members: Vec<NewMember>) -> Result<Vec<MemberObject>, Error> {
+        member::create_bulk_members(context, new_members)
+    }
+
+    pub fn update_one_member(context: &GraphQLContext, member: UpdateMember) -> Result<MemberObject, Error> {
+        member::update_one_member(context, member)
+    }
+
+    pub fn restore_member(context: &GraphQLContext, member_id: Uuid) -> Result<MemberObject, Error> {
+        member::restore_member(context, member_id)
+    }
+
+    pub fn create_team(context: &GraphQLContext, new_team: NewTeam) -> Result<TeamObject, Error> {
+        team::create_team(context, new_team)
+    }
+
+    pub fn create_bulk_teams(context: &GraphQLContext, new_teams: Vec<NewTeam>) -> Result<Vec<TeamObject>, Error> {
+        team::create_bulk_teams(context, new_teams)
+    }
+
+    pub fn update_team(context: &GraphQLContext, team: UpdateTeam) -> Result<TeamObject, Error> {
+        team::update_team(context, team)
+    }
+
+    pub fn remove_team(context: &GraphQLContext, team_id: Uuid) -> Result<bool, Error> {
+        team::remove_team(context, team_id)
+    }
+
+    pub fn restore_team(context: &GraphQLContext, team_id: Uuid) -> Result<TeamObject, Error> {
+        team::restore_team(context, team_id)
+    }
+
+    pub fn create_user(context: &GraphQLContext, new_user: NewUser) -> Result<RegisteredUserObject, Error> {
+        user::create_user(context, new_user)
+    }
+
+    pub fn create_bulk_users(context: &GraphQLContext, new_users: Vec<NewUser>) -> Result<Vec<RegisteredUserObject>, Error> {
+        user::create_bulk_users(context, new_users)
+    }
+
+    pub fn update_user(context: &GraphQLContext, user: UpdateUser) -> Result<UserObject, Error> {
+        user::update_user(context, user)
+    }
+
+    pub fn remove_user(context: &GraphQLContext, user_id: Uuid) -> Result<bool, Error> {
+        user::remove_user(context, user_id)
+    }
+
+    pub fn restore_user(context: &GraphQLContext, user_id: Uuid) -> Result<UserObject, Error> {
+        user::restore_user(context, user_id)
+    }
+}
+
+
+pub type Schema = RootNode<'static, Query, Mutation, EmptySubscription<GraphQLContext>>;
+
+pub fn schema() -> Schema {
+    Schema::new(Query, Mutation, EmptySubscription::new())
+}
+
+// The SDL of the schema for the client codegen. The types are registered in a hash map, so the definitions are sorted to keep
+// the same file between the builds.
+pub fn schema_language(schema: &Schema) -> String {
+    let mut document = schema.as_parser_document();
+    document.definitions.sort_by_cached_key(|definition| declaration(&definition.to_string()));
+    document.to_string()
+}
+
+// The line declaring the definition like "type Member {", the description printed above it is skipped.
+fn declaration(definition: &str) -> String {
+    const KEYWORDS: [&str; 7] = ["schema", "scalar", "type", "enum", "input", "union", "interface"];
+    definition
+        .lines()
+        .find(|line| KEYWORDS.iter().any(|keyword| line.starts_with(keyword)))
+        .unwrap_or(definition)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The snapshot is the SDL used by the clients, when the change of the schema is expected regenerate it with:
+    // cargo run -p graphql-service -- --write-schema graphql-service/schema.graphql
+    #[test]
+    fn schema_matches_the_snapshot() {
+        assert_eq!(
+            schema_language(&schema()),
+            include_str!("../../../schema.graphql"),
+            "The schema has changed, regenerate graphql-service/schema.graphql if the change is expected",
+        );
+    }
+}
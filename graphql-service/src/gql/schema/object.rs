@@ -0,0 +1,277 @@
+use chrono::NaiveDateTime;
+use diesel::PgConnection;
+use juniper::{GraphQLObject, GraphQLUnion};
+use uuid::Uuid;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::member::{find_memberships_by_user_id, list_team_members};
+use yugabyte::model::auth_user::AuthUserView;
+use yugabyte::model::dto::{ListQueryDTO, Page, PaginationDTO, RegisteredUserDTO};
+use yugabyte::model::member::Member;
+use yugabyte::model::role::Role;
+use yugabyte::model::search::SearchResult;
+use yugabyte::model::team::Team;
+use yugabyte::model::user::User;
+
+// The GraphQL objects of the records of the engine, they keep the names of the records in the schema and resolve their
+// relationships. The members are primed in the loader of the context when they are wrapped, so the teams and the users of a
+// list of members are loaded with one query each.
+pub(crate) struct MemberObject(pub(crate) Member);
+
+impl MemberObject {
+    pub(crate) fn list(members: Vec<Member>, context: &GraphQLContext) -> Vec<MemberObject> {
+        context.prime_members(&members);
+        members.into_iter().map(MemberObject).collect()
+    }
+}
+
+#[juniper::graphql_object(name = "Member", context = GraphQLContext)]
+impl MemberObject {
+    fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    fn team_id(&self) -> Uuid {
+        self.0.team_id
+    }
+
+    fn user_id(&self) -> Uuid {
+        self.0.user_id
+    }
+
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn identity_num(&self) -> &str {
+        &self.0.identity_num
+    }
+
+    fn role(&self) -> Role {
+        self.0.role
+    }
+
+    fn assigned_at(&self) -> NaiveDateTime {
+        self.0.assigned_at
+    }
+
+    fn expired_at(&self) -> Option<NaiveDateTime> {
+        self.0.expired_at
+    }
+
+    fn modification_date(&self) -> Option<NaiveDateTime> {
+        self.0.modification_date
+    }
+
+    fn deleted_at(&self) -> Option<NaiveDateTime> {
+        self.0.deleted_at
+    }
+
+    fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    fn team(&self, context: &GraphQLContext) -> Result<TeamObject, Error> {
+        context.load_team(&self.0.team_id).map(TeamObject)
+    }
+
+    fn user(&self, context: &GraphQLContext) -> Result<UserObject, Error> {
+        context.load_user(&self.0.user_id).map(UserObject)
+    }
+}
+
+pub(crate) struct TeamObject(pub(crate) Team);
+
+#[juniper::graphql_object(name = "Team", context = GraphQLContext)]
+impl TeamObject {
+    fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    fn deleted_at(&self) -> Option<NaiveDateTime> {
+        self.0.deleted_at
+    }
+
+    fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    // The members of the team with the same filters, sort and pagination of listMembers.
+    fn members(
+        &self,
+        pagination_dto: PaginationDTO,
+        list_query_dto: Option<ListQueryDTO>,
+        context: &GraphQLContext,
+    ) -> Result<MemberPageObject, Error> {
+        let pg_connection: &PgConnection = &*context.read_connection()?;
+
+        list_team_members(&self.0.id, &pagination_dto, &list_query_dto.unwrap_or_default(), pg_connection)
+            .map(|page| MemberPageObject::new(page, context))
+    }
+}
+
+pub(crate) struct UserObject(pub(crate) User);
+
+#[juniper::graphql_object(name = "User", context = GraphQLContext)]
+impl UserObject {
+    fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    fn email(&self) -> &str {
+        &self.0.email
+    }
+
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn deleted_at(&self) -> Option<NaiveDateTime> {
+        self.0.deleted_at
+    }
+
+    // The members of the user in every team, their teams are resolved by Member.team.
+    fn memberships(&self, context: &GraphQLContext) -> Result<Vec<MemberObject>, Error> {
+        let pg_connection: &PgConnection = &*context.read_connection()?;
+
+        find_memberships_by_user_id(&self.0.id, pg_connection).map(|members| MemberObject::list(members, context))
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "MemberPage", context = GraphQLContext)]
+pub(crate) struct MemberPageObject {
+    pub paginated_list: Vec<MemberObject>,
+    pub next_cursor: Option<String>,
+}
+
+impl MemberPageObject {
+    pub(crate) fn new(page: Page<Member>, context: &GraphQLContext) -> Self {
+        Self {
+            paginated_list: MemberObject::list(page.items, context),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "TeamPage", context = GraphQLContext)]
+pub(crate) struct TeamPageObject {
+    pub paginated_list: Vec<TeamObject>,
+    pub next_cursor: Option<String>,
+}
+
+impl From<Page<Team>> for TeamPageObject {
+    fn from(page: Page<Team>) -> Self {
+        Self {
+            paginated_list: page.items.into_iter().map(TeamObject).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "UserPage", context = GraphQLContext)]
+pub(crate) struct UserPageObject {
+    pub paginated_list: Vec<UserObject>,
+    pub next_cursor: Option<String>,
+}
+
+impl From<Page<User>> for UserPageObject {
+    fn from(page: Page<User>) -> Self {
+        Self {
+            paginated_list: page.items.into_iter().map(UserObject).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "RegisteredUserDTO", context = GraphQLContext)]
+pub(crate) struct RegisteredUserObject {
+    pub user: UserObject,
+    pub auth_user: AuthUserView,
+}
+
+impl From<RegisteredUserDTO> for RegisteredUserObject {
+    fn from(registered_user: RegisteredUserDTO) -> Self {
+        Self {
+            user: UserObject(registered_user.user),
+            auth_user: registered_user.auth_user,
+        }
+    }
+}
+
+// The highlight is the searched text HTML escaped, with the matched words wrapped in <mark> tags.
+#[derive(GraphQLObject)]
+#[graphql(name = "MemberSearchResult", context = GraphQLContext)]
+pub(crate) struct MemberSearchResultObject {
+    pub member: MemberObject,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "TeamSearchResult", context = GraphQLContext)]
+pub(crate) struct TeamSearchResultObject {
+    pub team: TeamObject,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(name = "UserSearchResult", context = GraphQLContext)]
+pub(crate) struct UserSearchResultObject {
+    pub user: UserObject,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(GraphQLUnion)]
+#[graphql(name = "SearchResult", context = GraphQLContext)]
+pub(crate) enum SearchResultObject {
+    Member(MemberSearchResultObject),
+    Team(TeamSearchResultObject),
+    User(UserSearchResultObject),
+}
+
+impl SearchResultObject {
+    pub(crate) fn list(search_results: Vec<SearchResult>, context: &GraphQLContext) -> Vec<SearchResultObject> {
+        context.prime_members(search_results.iter().filter_map(|search_result| match search_result {
+            SearchResult::Member(member_result) => Some(&member_result.member),
+            _ => None,
+        }));
+        search_results.into_iter().map(SearchResultObject::from).collect()
+    }
+}
+
+impl From<SearchResult> for SearchResultObject {
+    fn from(search_result: SearchResult) -> Self {
+        match search_result {
+            SearchResult::Member(result) => SearchResultObject::Member(MemberSearchResultObject {
+                member: MemberObject(result.member),
+                rank: result.rank,
+                highlight: result.highlight,
+            }),
+            SearchResult::Team(result) => SearchResultObject::Team(TeamSearchResultObject {
+                team: TeamObject(result.team),
+                rank: result.rank,
+                highlight: result.highlight,
+            }),
+            SearchResult::User(result) => SearchResultObject::User(UserSearchResultObject {
+                user: UserObject(result.user),
+                rank: result.rank,
+                highlight: result.highlight,
+            }),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use diesel::PgConnection;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::search as search_engine;
+use yugabyte::model::search::SearchDTO;
+
+use crate::gql::schema::object::SearchResultObject;
+
+pub(crate) fn search(search_dto: SearchDTO, context: &GraphQLContext) -> Result<Vec<SearchResultObject>, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    search_engine::search(&search_dto, pg_connection).map(|search_results| SearchResultObject::list(search_results, context))
+}
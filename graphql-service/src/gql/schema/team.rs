@@ -0,0 +1,96 @@
+use diesel::PgConnection;
+use uuid::Uuid;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::authorization::{authorize, authorize_team_restoration};
+use yugabyte::engine::retry::retry_transaction;
+use yugabyte::engine::team as team_engine;
+use yugabyte::engine::team::{delete_team_by_id, find_team_by_id, insert_bulk_team, list_all_teams, restore_team_by_id};
+use yugabyte::model::dto::{ListQueryDTO, PaginationDTO};
+use yugabyte::model::role::Permission;
+use yugabyte::model::team::{NewTeam, Team, UpdateTeam};
+
+use crate::gql::schema::{validate_input, validate_inputs};
+use crate::gql::schema::object::{TeamObject, TeamPageObject};
+
+pub(crate) fn list_teams(
+    pagination_dto: PaginationDTO,
+    list_query_dto: Option<ListQueryDTO>,
+    context: &GraphQLContext,
+) -> Result<TeamPageObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    list_all_teams(&pagination_dto, &list_query_dto.unwrap_or_default(), pg_connection).map(TeamPageObject::from)
+}
+
+pub(crate) fn find_team(team_id: Uuid, context: &GraphQLContext) -> Result<TeamObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    find_team_by_id(&team_id, pg_connection).map(TeamObject)
+}
+
+pub(crate) fn create_team(
+    context: &GraphQLContext,
+    new_team: NewTeam,
+) -> Result<TeamObject, Error> {
+    validate_input(&new_team)?;
+    let claims = context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+
+    new_team.insert_team(claims, pg_connection).map(TeamObject)
+}
+
+pub(crate) fn create_bulk_teams(
+    context: &GraphQLContext,
+    new_teams: Vec<NewTeam>,
+) -> Result<Vec<TeamObject>, Error> {
+    validate_inputs(&new_teams)?;
+    let claims = context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+
+    let teams: Vec<Team> = new_teams
+        .into_iter()
+        .map(|new_team| Team {
+            id: Uuid::new_v4(),
+            name: new_team.name,
+            description: new_team.description,
+            deleted_at: None,
+            version: 1,
+        })
+        .collect();
+
+    insert_bulk_team(&teams, claims, pg_connection).map(|teams| teams.into_iter().map(TeamObject).collect())
+}
+
+// Only the team owner can update, remove or restore the team.
+pub(crate) fn update_team(
+    context: &GraphQLContext,
+    team: UpdateTeam,
+) -> Result<TeamObject, Error> {
+    validate_input(&team)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize(context.authenticated_claims()?, &team.id, Permission::ManageTeam, pg_connection)?;
+
+    team_engine::update_team(&team, pg_connection).map(TeamObject)
+}
+
+pub(crate) fn remove_team(context: &GraphQLContext, team_id: Uuid) -> Result<bool, Error> {
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize(context.authenticated_claims()?, &team_id, Permission::ManageTeam, pg_connection)?;
+
+    delete_team_by_id(&team_id, pg_connection).map(|_| true)
+}
+
+// The restoration is checked against the roles of the members deleted with the team, before restoring it.
+pub(crate) fn restore_team(context: &GraphQLContext, team_id: Uuid) -> Result<TeamObject, Error> {
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    let claims = context.authenticated_claims()?;
+
+    retry_transaction(pg_connection, || {
+        authorize_team_restoration(claims, &team_id, pg_connection)?;
+        restore_team_by_id(&team_id, pg_connection).map(TeamObject)
+    })
+}
@@ -0,0 +1,87 @@
+use diesel::PgConnection;
+use uuid::Uuid;
+
+use error::error::Error;
+use yugabyte::context::GraphQLContext;
+use yugabyte::engine::authorization::authorize_user_change;
+use yugabyte::engine::user::{
+    delete_user_by_id, find_user_by_id, list_all_users, register_bulk_users, restore_user_by_id,
+};
+use yugabyte::model::dto::{ListQueryDTO, PaginationDTO};
+use yugabyte::model::user::{NewUser, UpdateUser};
+
+use crate::gql::schema::{validate_input, validate_inputs};
+use crate::gql::schema::object::{RegisteredUserObject, UserObject, UserPageObject};
+
+pub(crate) fn list_users(
+    pagination_dto: PaginationDTO,
+    list_query_dto: Option<ListQueryDTO>,
+    context: &GraphQLContext,
+) -> Result<UserPageObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    list_all_users(&pagination_dto, &list_query_dto.unwrap_or_default(), pg_connection).map(UserPageObject::from)
+}
+
+pub(crate) fn find_user(user_id: Uuid, context: &GraphQLContext) -> Result<UserObject, Error> {
+    context.authenticated_claims()?;
+    let pg_connection: &PgConnection = &*context.read_connection()?;
+
+    find_user_by_id(&user_id, pg_connection).map(UserObject)
+}
+
+// Create the User and its AuthUser with the same id, the public registration is the registerUser mutation.
+pub(crate) fn create_user(
+    context: &GraphQLContext,
+    new_user: NewUser,
+) -> Result<RegisteredUserObject, Error> {
+    context.authenticated_claims()?;
+    validate_input(&new_user)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    let auth_pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    new_user.register_user(pg_connection, auth_pg_connection).map(RegisteredUserObject::from)
+}
+
+pub(crate) fn create_bulk_users(
+    context: &GraphQLContext,
+    new_users: Vec<NewUser>,
+) -> Result<Vec<RegisteredUserObject>, Error> {
+    context.authenticated_claims()?;
+    validate_inputs(&new_users)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    let auth_pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    register_bulk_users(&new_users, pg_connection, auth_pg_connection)
+        .map(|registered_users| registered_users.into_iter().map(RegisteredUserObject::from).collect())
+}
+
+// The users can change, remove or restore themselves only.
+pub(crate) fn update_user(
+    context: &GraphQLContext,
+    user: UpdateUser,
+) -> Result<UserObject, Error> {
+    validate_input(&user)?;
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize_user_change(context.authenticated_claims()?, &user.id, pg_connection)?;
+    let auth_pg_connection: &PgConnection = &*context.auth_pool.get()?;
+
+    user.update_user(pg_connection, auth_pg_connection).map(UserObject)
+}
+
+pub(crate) fn remove_user(context: &GraphQLContext, user_id: Uuid) -> Result<bool, Error> {
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+    authorize_user_change(context.authenticated_claims()?, &user_id, pg_connection)?;
+
+    delete_user_by_id(&user_id, pg_connection).map(|_| true)
+}
+
+pub(crate) fn restore_user(context: &GraphQLContext, user_id: Uuid) -> Result<UserObject, Error> {
+    if user_id != context.authenticated_claims()?.sub {
+        return Err(Error::Forbidden("permission-denied".to_string()));
+    }
+    let pg_connection: &PgConnection = &*context.pool.get()?;
+
+    restore_user_by_id(&user_id, pg_connection).map(UserObject)
+}
@@ -1,21 +1,36 @@
-use std::io;
+use std::{env, io, process};
 
 use actix_web::{App, HttpServer, middleware};
 use actix_web::web::{Data, JsonConfig};
 
-use yugabyte::db_connection::CoreDBPool;
+use yugabyte::db_connection::{AuthDBPool, CoreDBPool};
+use yugabyte::migration::run_migrations_if_enabled;
+use yugabyte::util::jwt::check_jwt_secret;
 
-use crate::gql::{logging_setup, routes};
+use crate::gql::{logging_setup, routes, write_schema};
 
 mod gql;
 
 #[actix_web::main]
 async fn main() -> io::Result<()> {
 
+    // "--write-schema [path]" writes the SDL of the schema for the client codegen and exits without starting the server.
+    let args: Vec<String> = env::args().collect();
+    if let Some(flag_index) = args.iter().position(|arg| arg == "--write-schema") {
+        return write_schema(args.get(flag_index + 1).map_or("schema.graphql", String::as_str));
+    }
+
     logging_setup();
+    check_jwt_secret().unwrap_or_else(|err| exit_with_diagnostic(err));
+
+    // Instantiate the connection pools of the core and auth databases
+    let core_db_pool = CoreDBPool::try_default().unwrap_or_else(|err| exit_with_diagnostic(err));
+    let auth_db_pool = AuthDBPool::try_default().unwrap_or_else(|err| exit_with_diagnostic(err));
 
-    // Instantiate a new connection pool
-    let core_db_pool_data = Data::new(CoreDBPool::default().0);
+    // Apply the pending migrations of both databases before serving the requests
+    run_migrations_if_enabled(&core_db_pool, &auth_db_pool).unwrap_or_else(|err| exit_with_diagnostic(err));
+    let core_db_pool_data = Data::new(core_db_pool);
+    let auth_db_pool_data = Data::new(auth_db_pool);
 
     // Start up the server, passing in (a) the connection pool
     // to make it available to all endpoints and (b) the configuration
@@ -24,6 +39,7 @@ async fn main() -> io::Result<()> {
         App::new()
             .app_data(Data::new(JsonConfig::default().limit(4096)))
             .app_data(core_db_pool_data.clone())
+            .app_data(auth_db_pool_data.clone())
             .wrap(middleware::Logger::default())
             .configure(routes)
     })
@@ -31,3 +47,9 @@ async fn main() -> io::Result<()> {
         .run()
         .await
 }
+
+// Stop the startup with a readable message instead of a panic backtrace.
+fn exit_with_diagnostic(diagnostic: String) -> ! {
+    eprintln!("Startup failed: {}", diagnostic);
+    process::exit(1)
+}
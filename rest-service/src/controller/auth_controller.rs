@@ -0,0 +1,100 @@
+use actix_web::web;
+use actix_web::web::Json;
+
+use error::error::ServerErrorResponse;
+use yugabyte::db_connection::AuthDBPool;
+use yugabyte::engine::auth_user::{find_auth_user_by_id, verify_auth_user_password};
+use yugabyte::engine::execution::execute;
+use yugabyte::engine::refresh_token::{issue_refresh_token, revoke_refresh_token, rotate_refresh_token};
+use yugabyte::model::auth_user::AuthUser;
+use yugabyte::model::dto::{LoginDTO, RefreshTokenDTO, SuccessResponse, TokenPairDTO};
+use yugabyte::util::jwt::{ACCESS_TOKEN_TTL, encode_access_token};
+
+use crate::controller::auth_guard::{AuthenticatedUser, unauthorized};
+
+pub(crate) async fn login_api(
+    login_dto: Json<LoginDTO>,
+    pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<TokenPairDTO>>, ServerErrorResponse> {
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: Verify the credentials against the stored password hash.
+        match verify_auth_user_password(&login_dto.email, &login_dto.password, pg_connection)? {
+            Some(authenticated_user) => {
+                // Step 2: Issue a new refresh token.
+                let (raw_refresh_token, _) = issue_refresh_token(&authenticated_user.id, pg_connection)?;
+                Ok(Some((authenticated_user, raw_refresh_token)))
+            }
+            None => Ok(None),
+        }
+    }).await;
+
+    match result {
+        // Step 3: Fire the token pair.
+        Ok(Some((authenticated_user, raw_refresh_token))) => Ok(Json(SuccessResponse {
+            message: format!("Successfully logged in."),
+            data: token_pair(&authenticated_user, raw_refresh_token)?,
+        })),
+        Ok(None) => Err(unauthorized("invalid-credentials")),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn refresh_api(
+    refresh_token_dto: Json<RefreshTokenDTO>,
+    pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<TokenPairDTO>>, ServerErrorResponse> {
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: Revoke the presented refresh token and issue a new one.
+        match rotate_refresh_token(&refresh_token_dto.refresh_token, pg_connection)? {
+            Some((raw_refresh_token, rotated_refresh_token)) => {
+                // Step 2: Find the auth_user of the refresh token to sign the new access token.
+                let found_auth_user = find_auth_user_by_id(&rotated_refresh_token.auth_user_id, pg_connection)?;
+                Ok(Some((found_auth_user, raw_refresh_token)))
+            }
+            None => Ok(None),
+        }
+    }).await;
+
+    match result {
+        // Step 3: Fire the token pair.
+        Ok(Some((found_auth_user, raw_refresh_token))) => Ok(Json(SuccessResponse {
+            message: format!("Successfully refreshed the tokens."),
+            data: token_pair(&found_auth_user, raw_refresh_token)?,
+        })),
+        Ok(None) => Err(unauthorized("invalid-refresh-token")),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn logout_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    refresh_token_dto: Json<RefreshTokenDTO>,
+    pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
+    // Step 1: Revoke the refresh token if it belongs to the authenticated user.
+    let result = execute(pool, move |pg_connection| {
+        revoke_refresh_token(&refresh_token_dto.refresh_token, &claims.sub, pg_connection)
+    }).await;
+
+    match result {
+        // Step 2: Fire the response.
+        Ok(true) => Ok(Json(SuccessResponse {
+            message: format!("Successfully logged out."),
+            data: true,
+        })),
+        Ok(false) => Err(unauthorized("invalid-refresh-token")),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+fn token_pair(authenticated_user: &AuthUser, refresh_token: String) -> Result<TokenPairDTO, ServerErrorResponse> {
+    match encode_access_token(authenticated_user) {
+        Ok(access_token) => Ok(TokenPairDTO {
+            access_token,
+            refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: ACCESS_TOKEN_TTL.num_seconds(),
+        }),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
@@ -0,0 +1,60 @@
+use std::future::{Future, ready, Ready};
+
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use actix_web::body::EitherBody;
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{AUTHORIZATION, HeaderMap};
+
+use error::error::{ErrorCode, ServerErrorResponse};
+use yugabyte::util::jwt::{Claims, decode_access_token};
+
+// The claims of the access token of the current request, it can be used as a handler argument.
+pub(crate) struct AuthenticatedUser(pub Claims);
+
+impl FromRequest for AuthenticatedUser {
+    type Error = ServerErrorResponse;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        // The claims are already decoded if the request went through the authentication middleware.
+        let claims = match req.extensions().get::<Claims>() {
+            Some(claims) => Ok(claims.clone()),
+            None => decode_bearer_token(req.headers()),
+        };
+        ready(claims.map(AuthenticatedUser))
+    }
+}
+
+// Reject the request with 401 if it doesn't carry a valid access token in the "Authorization: Bearer" header.
+pub(crate) fn require_authentication<S, B>(
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output=Result<ServiceResponse<EitherBody<B>>, Error>>
+    where S: Service<ServiceRequest, Response=ServiceResponse<B>, Error=Error> {
+    let response = match decode_bearer_token(req.headers()) {
+        Ok(claims) => {
+            req.extensions_mut().insert(claims);
+            Ok(srv.call(req))
+        }
+        Err(err) => Err(req.error_response(err)),
+    };
+    async move {
+        match response {
+            Ok(response) => response.await.map(ServiceResponse::map_into_left_body),
+            Err(error_response) => Ok(error_response.map_into_right_body()),
+        }
+    }
+}
+
+fn decode_bearer_token(headers: &HeaderMap) -> Result<Claims, ServerErrorResponse> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|header_value| header_value.to_str().ok())
+        .and_then(|header_value| header_value.strip_prefix("Bearer "))
+        .ok_or_else(|| unauthorized("missing-access-token"))?;
+    decode_access_token(token.trim()).map_err(|_| unauthorized("invalid-access-token"))
+}
+
+pub(crate) fn unauthorized(code: &str) -> ServerErrorResponse {
+    ServerErrorResponse::Unauthorized(ErrorCode::new(code))
+}
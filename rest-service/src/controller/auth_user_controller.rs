@@ -2,130 +2,81 @@ use actix_web::web;
 use actix_web::web::{Json, Query};
 use uuid::Uuid;
 
-use error::error::{ErrorCodesWrapper, ServerErrorResponse};
-use yugabyte::db_connection::{CoreDBPool, pgdata_to_pgconnection};
-use yugabyte::engine::auth_user::{
-    count_auth_users, delete_all_auth_users, delete_auth_user_by_id, find_auth_user_by_id, insert_bulk_auth_users,
-    list_all_auth_users
-};
-use yugabyte::engine::member::delete_all_members;
-use yugabyte::model::auth_user::AuthUser;
+use error::error::{Error, ServerErrorResponse};
+use yugabyte::db_connection::AuthDBPool;
+use yugabyte::engine::auth_user::{count_auth_users, delete_auth_user_by_id, find_auth_user_by_id, list_all_auth_users};
+use yugabyte::engine::execution::execute;
+use yugabyte::model::auth_user::AuthUserView;
 use yugabyte::model::dto::{PaginatedResponseDTO, PaginationDTO, SuccessResponse};
-use yugabyte::model::user::NewUser;
+
+use crate::controller::auth_guard::AuthenticatedUser;
 
 pub(crate) async fn list_auth_users_api(
     Query(pagination_dto): Query<PaginationDTO>,
-    pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<PaginatedResponseDTO<AuthUser>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Count all auth_users.
-    match count_auth_users(&pg_connection) {
-        Ok(auth_users_count) => {
-            // Step 3: List all paginated auth_users.
-            match list_all_auth_users(&pagination_dto, &pg_connection) {
-                Ok(paginated_list) => {
-                    let response = PaginatedResponseDTO {
-                        paginated_list,
-                        count: auth_users_count,
-                    };
+    pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<PaginatedResponseDTO<AuthUserView>>>, ServerErrorResponse> {
+    // Step 1: Count all auth_users and list the paginated auth_users.
+    let result = execute(pool, move |pg_connection| {
+        let auth_users_count = count_auth_users(pg_connection)?;
+        let page = list_all_auth_users(&pagination_dto, pg_connection)?;
+        Ok(PaginatedResponseDTO {
+            paginated_list: page.items.into_iter().map(AuthUserView::from).collect(),
+            count: auth_users_count,
+            next_cursor: page.next_cursor,
+        })
+    }).await;
 
-                    // Step 4: Fire the response.
-                    Ok(Json(SuccessResponse {
-                        message: format!("Successfully retrieved all auth users."),
-                        data: response,
-                    }))
-                }
-                Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-            }
-        }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-    }
-}
-
-pub(crate) async fn insert_auth_user_api(
-    new_user: Json<NewUser>,
-    pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<AuthUser>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Insert the Auth_User into the database
-    match new_user.add_auth_user(&pg_connection) {
-        // Step 3: Fire the inserted auth_user
-        Ok(inserted_auth_user) => Ok(Json(SuccessResponse {
-            message: format!("Successfully added the new Auth User."),
-            data: inserted_auth_user,
+    match result {
+        // Step 2: Fire the response.
+        Ok(response) => Ok(Json(SuccessResponse {
+            message: format!("Successfully retrieved all auth users."),
+            data: response,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn remove_auth_user_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
     auth_user_id: web::Path<Uuid>,
-    pool: web::Data<CoreDBPool>,
+    pool: web::Data<AuthDBPool>,
 ) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+    // Step 1: The users can delete their own credentials only.
+    let auth_user_id = auth_user_id.into_inner();
+    if auth_user_id != claims.sub {
+        return Err(ServerErrorResponse::from(Error::Forbidden("permission-denied".to_string())));
+    }
 
     // Step 2: Delete the auth_user from the database.
-    if !delete_auth_user_by_id(&auth_user_id.into_inner(), &pg_connection) {
-        Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes()))
-    } else {
+    let result = execute(pool, move |pg_connection| {
+        delete_auth_user_by_id(&auth_user_id, pg_connection).map(|_| true)
+    }).await;
+
+    match result {
         // Step 3: Fire the response.
-        Ok(Json(SuccessResponse {
+        Ok(_) => Ok(Json(SuccessResponse {
             message: format!("Successfully deleted the auth_user."),
             data: true,
-        }))
-    }
-}
-
-pub(crate) async fn remove_all_auth_users_api(
-    pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Delete all auth_users from the database.
-    match delete_all_auth_users(&pg_connection) {
-        Ok(deleted_auth_users) => {
-            // Step 3: Delete all members users, and teams from the database.
-            if delete_all_members(&pg_connection).is_ok() {
-                // Step 4: Fire the response.
-                Ok(Json(SuccessResponse {
-                    message: format!("Successfully deleted all auth_users."),
-                    data: true,
-                }))
-            } else {
-                // Step 3: In case an error happened while deleting members users, and teams, I will insert the deleted auth users again.
-                match insert_bulk_auth_users(&deleted_auth_users, &pg_connection) {
-                    Ok(_) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes())),
-                    Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-                }
-            }
-        }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn find_auth_user_by_id_api(
     auth_user_id: web::Path<Uuid>,
-    pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<AuthUser>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Find the auth_user from the database.
-    match find_auth_user_by_id(&auth_user_id.into_inner(), &pg_connection) {
+    pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<AuthUserView>>, ServerErrorResponse> {
+    // Step 1: Find the auth_user from the database.
+    let auth_user_id = auth_user_id.into_inner();
+    match execute(pool, move |pg_connection| find_auth_user_by_id(&auth_user_id, pg_connection)).await {
         Ok(found_auth_user) => {
-            // Step 3: Fire the response
+            // Step 2: Fire the response
             Ok(Json(SuccessResponse {
                 message: format!("Successfully found the Auth User."),
-                data: found_auth_user,
+                data: AuthUserView::from(found_auth_user),
             }))
         }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
@@ -0,0 +1,49 @@
+use actix_web::HttpRequest;
+use actix_web::http::header::{ETag, EntityTag, Header, IF_MATCH, IfMatch};
+
+use error::error::Error;
+
+// The version of the record is exposed as a strong entity tag like "3".
+pub(crate) fn version_etag(version: i32) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+// The version in the If-Match header takes precedence over the version of the payload. "*" is rejected, it would match any
+// version of the record while the update is always checked against the version read by the client.
+pub(crate) fn expected_version(req: &HttpRequest, payload_version: i32) -> Result<i32, Error> {
+    if !req.headers().contains_key(IF_MATCH) {
+        return Ok(payload_version);
+    }
+    match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => Err(Error::BadRequest("if-match-any-error".to_string())),
+        Ok(IfMatch::Items(entity_tags)) => match entity_tags.as_slice() {
+            [entity_tag] if !entity_tag.weak => entity_tag
+                .tag()
+                .parse::<i32>()
+                .map_err(|_| Error::BadRequest("invalid-if-match".to_string())),
+            _ => Err(Error::BadRequest("invalid-if-match".to_string())),
+        },
+        Err(_) => Err(Error::BadRequest("invalid-if-match".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn if_match_overrides_the_payload_version() {
+        let req = TestRequest::default().insert_header((IF_MATCH, "\"4\"")).to_http_request();
+        assert_eq!(expected_version(&req, 1).unwrap(), 4);
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(expected_version(&req, 1).unwrap(), 1);
+        let req = TestRequest::default().insert_header((IF_MATCH, "*")).to_http_request();
+        assert!(matches!(expected_version(&req, 1), Err(Error::BadRequest(code)) if code == "if-match-any-error"));
+        for if_match in ["W/\"4\"", "\"four\"", "\"1\", \"2\"", "4"] {
+            let req = TestRequest::default().insert_header((IF_MATCH, if_match)).to_http_request();
+            assert!(matches!(expected_version(&req, 1), Err(Error::BadRequest(_))), "{} is accepted", if_match);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::future::{Ready, ready};
+
+use actix_web::{FromRequest, HttpRequest};
+use actix_web::dev::Payload;
+use percent_encoding::percent_decode_str;
+
+use error::error::{Error, ErrorCode, ServerErrorResponse};
+use yugabyte::model::dto::{FilterDTO, FilterOperator, ListQueryDTO, SortDTO};
+
+// The pagination parameters are read by the Query<PaginationDTO> extractor of the same request.
+const PAGINATION_KEYS: [&str; 3] = ["page_size", "offset", "cursor"];
+
+// The operators ending with "=" are split by the "=" of the pair, so their first character ends the key like "name~".
+const KEY_OPERATORS: [(char, FilterOperator); 4] = [
+    ('>', FilterOperator::Gte),
+    ('<', FilterOperator::Lte),
+    ('!', FilterOperator::Ne),
+    ('~', FilterOperator::Contains),
+];
+
+// The filters and the sort of the list endpoints, read from a query string like "role=admin&assigned_at%3E=2022-02-28&sort=-assigned_at,name".
+pub(crate) struct ListQuery(pub ListQueryDTO);
+
+impl FromRequest for ListQuery {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(match parse_list_query(req.query_string()) {
+            Ok(list_query_dto) => Ok(ListQuery(list_query_dto)),
+            Err(err) => Err(ServerErrorResponse::from(err).into()),
+        })
+    }
+}
+
+fn parse_list_query(query_string: &str) -> Result<ListQueryDTO, Error> {
+    let mut list_query_dto = ListQueryDTO::default();
+    for pair in query_string.split('&').filter(|pair| !pair.is_empty()) {
+        let (field, operator, value) = parse_filter(pair)?;
+        if PAGINATION_KEYS.contains(&field.as_str()) {
+            continue;
+        }
+        if field == "sort" {
+            if operator != FilterOperator::Eq {
+                return Err(query_error(&field));
+            }
+            list_query_dto.sort.extend(value.split(',').filter(|sort_field| !sort_field.is_empty()).map(|sort_field| {
+                match sort_field.strip_prefix('-') {
+                    Some(sort_field) => SortDTO { field: sort_field.to_string(), descending: true },
+                    None => SortDTO { field: sort_field.to_string(), descending: false },
+                }
+            }));
+            continue;
+        }
+        list_query_dto.filters.push(FilterDTO {
+            field,
+            operator,
+            value: if value == "null" { None } else { Some(value) },
+        });
+    }
+    Ok(list_query_dto)
+}
+
+// The clients send ">" and "<" encoded as "%3E" and "%3C", so the operator is read after decoding the key.
+fn parse_filter(pair: &str) -> Result<(String, FilterOperator, String), Error> {
+    match pair.split_once('=') {
+        Some((raw_key, raw_value)) => {
+            let key = decode(raw_key)?;
+            let value = decode(raw_value)?;
+            match KEY_OPERATORS.iter().find(|(suffix, _)| key.ends_with(*suffix)) {
+                Some((_, operator)) => Ok((key[..key.len() - 1].to_string(), *operator, value)),
+                None => Ok((key, FilterOperator::Eq, value)),
+            }
+        }
+        None => {
+            let key = decode(pair)?;
+            if let Some((field, value)) = key.split_once('>') {
+                Ok((field.to_string(), FilterOperator::Gt, value.to_string()))
+            } else if let Some((field, value)) = key.split_once('<') {
+                Ok((field.to_string(), FilterOperator::Lt, value.to_string()))
+            } else {
+                Err(query_error(&key))
+            }
+        }
+    }
+}
+
+fn decode(raw: &str) -> Result<String, Error> {
+    percent_decode_str(&raw.replace('+', " "))
+        .decode_utf8()
+        .map(|decoded| decoded.to_string())
+        .map_err(|_| query_error(raw))
+}
+
+fn query_error(field: &str) -> Error {
+    Error::ValidationError(vec![ErrorCode {
+        code: "filter-format-error".to_string(),
+        field: Some(field.to_string()),
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_and_sort_are_parsed() {
+        let list_query_dto = parse_list_query(
+            "page_size=10&role=admin&assigned_at%3E=2022-02-28&expired_at=null&name~=a%26li&team_id!=x&assigned_at%3C2022-03-01&sort=-assigned_at,name",
+        ).unwrap();
+        let filters: Vec<(&str, FilterOperator, Option<&str>)> = list_query_dto
+            .filters
+            .iter()
+            .map(|filter| (filter.field.as_str(), filter.operator, filter.value.as_deref()))
+            .collect();
+        assert_eq!(filters, [
+            ("role", FilterOperator::Eq, Some("admin")),
+            ("assigned_at", FilterOperator::Gte, Some("2022-02-28")),
+            ("expired_at", FilterOperator::Eq, None),
+            ("name", FilterOperator::Contains, Some("a&li")),
+            ("team_id", FilterOperator::Ne, Some("x")),
+            ("assigned_at", FilterOperator::Lt, Some("2022-03-01")),
+        ]);
+        let sort: Vec<(&str, bool)> = list_query_dto.sort.iter().map(|sort_dto| (sort_dto.field.as_str(), sort_dto.descending)).collect();
+        assert_eq!(sort, [("assigned_at", true), ("name", false)]);
+        assert!(parse_list_query("role").is_err());
+    }
+}
@@ -1,38 +1,45 @@
-use actix_web::web;
+use actix_web::{CustomizeResponder, HttpRequest, Responder, web};
 use actix_web::web::{Json, Path, Query};
 use uuid::Uuid;
 
-use error::error::{ErrorCodesWrapper, ServerErrorResponse};
-use yugabyte::db_connection::{CoreDBPool, pgdata_to_pgconnection};
-use yugabyte::engine::member::{count_members, delete_all_members, delete_member_by_id, filter_members_by_name,
-                               find_member_by_id, get_all_member_names_by_team_id, insert_bulk_members, list_all_members
+use error::error::ServerErrorResponse;
+use yugabyte::db_connection::CoreDBPool;
+use yugabyte::engine::authorization::{authorize_member_change, authorize_member_role};
+use yugabyte::engine::execution::{execute, execute_read};
+use yugabyte::engine::member::{count_members, delete_member_by_id, filter_members_by_name,
+                               find_member_by_id, get_all_member_names_by_team_id, insert_bulk_members, list_all_members,
+                               restore_member_by_id, update_member,
 };
+use yugabyte::engine::retry::retry_transaction;
 use yugabyte::engine::user::find_user_by_id;
 use yugabyte::model::dto::{MemberEmail, MemberInfo, MemberName, PaginatedResponseDTO, PaginationDTO, SuccessResponse};
-use yugabyte::model::member::{Member, Name, NewMember};
+use yugabyte::model::member::{Member, Name, NewMember, UpdateMember};
 use yugabyte::util::utils::current_timestamp;
 
+use crate::controller::etag::{expected_version, version_etag};
+use crate::controller::list_query::ListQuery;
+use crate::controller::validated_json::{ValidatedJson, ValidatedJsonList};
+use crate::controller::auth_guard::AuthenticatedUser;
+
 pub(crate) async fn find_member_email_api(
     user_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<MemberEmail>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Find the user from the database.
-    match find_user_by_id(&user_id.into_inner(), &pg_connection) {
+    // Step 1: Find the user from the database.
+    let user_id = user_id.into_inner();
+    match execute_read(pool, move |pg_connection| find_user_by_id(&user_id, pg_connection)).await {
         Ok(found_user) => {
             let member_email = MemberEmail {
                 name: found_user.name,
                 email: found_user.email,
             };
-            // Step 3: Fire the response
+            // Step 2: Fire the response
             Ok(Json(SuccessResponse {
                 message: format!("Successfully find the Member Email."),
                 data: member_email,
             }))
         }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
@@ -42,168 +49,217 @@ pub(crate) async fn find_member_info_api(
 ) -> Result<Json<SuccessResponse<MemberInfo>>, ServerErrorResponse> {
     let (user_id, member_id) = path.into_inner();
 
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+    // Step 1: Find the user and the member from the database.
+    let result = execute_read(pool, move |pg_connection| {
+        let found_user = find_user_by_id(&user_id, pg_connection)?;
+        let found_member = find_member_by_id(&member_id, pg_connection)?;
+        Ok(MemberInfo {
+            name: found_user.name,
+            email: found_user.email,
+            identity_num: found_member.identity_num,
+            role: found_member.role,
+        })
+    }).await;
 
-    // Step 2: Find the user from the database.
-    match find_user_by_id(&user_id, &pg_connection) {
-        Ok(found_user) => {
-            // Step 3: Find the member from the database.
-            match find_member_by_id(&member_id, &pg_connection) {
-                Ok(found_member) => {
-                    let member_info = MemberInfo {
-                        name: found_user.name,
-                        email: found_user.email,
-                        identity_num: found_member.identity_num,
-                        role: found_member.role,
-                    };
-                    // Step 4: Fire the response
-                    Ok(Json(SuccessResponse {
-                        message: format!("Successfully find the Member Info."),
-                        data: member_info,
-                    }))
-                }
-                Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-            }
+    match result {
+        // Step 2: Fire the response
+        Ok(member_info) => Ok(Json(SuccessResponse {
+            message: format!("Successfully find the Member Info."),
+            data: member_info,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn find_member_by_id_api(
+    member_id: web::Path<Uuid>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<CustomizeResponder<Json<SuccessResponse<Member>>>, ServerErrorResponse> {
+    // Step 1: Find the member from the database.
+    let member_id = member_id.into_inner();
+    match execute_read(pool, move |pg_connection| find_member_by_id(&member_id, pg_connection)).await {
+        Ok(found_member) => {
+            // Step 2: Fire the response with the version of the member to be sent back as If-Match on its update.
+            let etag = version_etag(found_member.version);
+            Ok(Json(SuccessResponse {
+                message: format!("Successfully found the Member."),
+                data: found_member,
+            }).customize().insert_header(etag))
         }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn list_members_api(
     Query(pagination_dto): Query<PaginationDTO>,
+    ListQuery(list_query_dto): ListQuery,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<PaginatedResponseDTO<Member>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Count all members.
-    match count_members(&pg_connection) {
-        Ok(members_count) => {
-            // Step 3: List all paginated members.
-            match list_all_members(&pagination_dto, &pg_connection) {
-                Ok(paginated_list) => {
-                    let response = PaginatedResponseDTO {
-                        paginated_list,
-                        count: members_count,
-                    };
-
-                    // Step 4: Fire the response.
-                    Ok(Json(SuccessResponse {
-                        message: format!("Successfully retrieved all members."),
-                        data: response,
-                    }))
-                }
-                Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-            }
-        }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+    // Step 1: Count all members and list the paginated members.
+    let result = execute_read(pool, move |pg_connection| {
+        let members_count = count_members(&list_query_dto, pg_connection)?;
+        let page = list_all_members(&pagination_dto, &list_query_dto, pg_connection)?;
+        Ok(PaginatedResponseDTO {
+            paginated_list: page.items,
+            count: members_count,
+            next_cursor: page.next_cursor,
+        })
+    }).await;
+
+    match result {
+        // Step 2: Fire the response.
+        Ok(response) => Ok(Json(SuccessResponse {
+            message: format!("Successfully retrieved all members."),
+            data: response,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn insert_member_api(
-    new_member: Json<NewMember>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    new_member: ValidatedJson<NewMember>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Member>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: Check that the authenticated user can add a member with this role to the team.
+        authorize_member_role(&claims, &new_member.team_id, &new_member.role, pg_connection)?;
+
+        // Step 2: Insert the member into the database
+        new_member.insert_member(pg_connection)
+    }).await;
 
-    // Step 2: Insert the member into the database
-    match new_member.insert_member(&pg_connection) {
+    match result {
         // Step 3: Fire the inserted member
         Ok(inserted_member) => Ok(Json(SuccessResponse {
             message: format!("Successfully added the new Member."),
             data: inserted_member,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn insert_bulk_members_api(
-    new_members: Json<Vec<NewMember>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    new_members: ValidatedJsonList<NewMember>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Vec<Member>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-    let mut members = Vec::new();
-
-    // Step 2: Iterate over the New Members and create the list of members to be added in a bulk not to load the execution time of the database.
-    for new_member in new_members.0 {
-        let member = Member {
-            id: Uuid::new_v4(),
-            team_id: new_member.team_id,
-            user_id: new_member.user_id,
-            name: new_member.name.clone(),
-            identity_num: new_member.identity_num.clone(),
-            role: new_member.role.clone(),
-            assigned_at: current_timestamp(),
-            expired_at: new_member.expired_at,
-            modification_date: None,
-        };
-        members.push(member);
-    }
+    let result = execute(pool, move |pg_connection| {
+        let mut members = Vec::new();
+
+        // Step 1: Iterate over the New Members and create the list of members to be added in a bulk not to load the execution time of the database.
+        for new_member in new_members.0 {
+            // Step 2: Check that the authenticated user can add a member with this role to the team.
+            authorize_member_role(&claims, &new_member.team_id, &new_member.role, pg_connection)?;
+            let member = Member {
+                id: Uuid::new_v4(),
+                team_id: new_member.team_id,
+                user_id: new_member.user_id,
+                name: new_member.name.clone(),
+                identity_num: new_member.identity_num.clone(),
+                role: new_member.role,
+                assigned_at: current_timestamp(),
+                expired_at: new_member.expired_at,
+                modification_date: None,
+                deleted_at: None,
+                version: 1,
+            };
+            members.push(member);
+        }
+
+        // Step 3: Insert the bulk of members into the database.
+        insert_bulk_members(&members, pg_connection)
+    }).await;
 
-    /*new_members.0.iter().map(|new_member| {
-        let member = Member {
-            id: Uuid::new_v4(),
-            team_id: new_member.team_id,
-            user_id: new_member.user_id,
-            name: new_member.name.clone(),  // I cloned the name only not the whole new_member object because the string is located in the heap memory.
-            identity_num: new_member.identity_num.clone(),
-            role: new_member.role.clone(),
-            assigned_at: current_timestamp(),
-            expired_at: new_member.expired_at,
-            modification_date: None,
-        };
-        members.push(member);
-        members.clone()
-    });*/
-
-    // Step 4: Insert the bulk of members into the database.
-    match insert_bulk_members(&members, &pg_connection) {
-        // Step 5: Fire the inserted members
+    match result {
+        // Step 4: Fire the inserted members
         Ok(inserted_members) => Ok(Json(SuccessResponse {
             message: format!("Successfully added the bulk of Members."),
             data: inserted_members,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn update_member_api(
+    req: HttpRequest,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    incoming_member: ValidatedJson<UpdateMember>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<CustomizeResponder<Json<SuccessResponse<Member>>>, ServerErrorResponse> {
+    // Step 1: The version of the If-Match header is the expected version of the member.
+    let mut incoming_member = incoming_member.0;
+    incoming_member.version = expected_version(&req, incoming_member.version)?;
+
+    let result = execute(pool, move |pg_connection| {
+        // Step 2: Check that the authenticated user can manage the member with its current and new roles.
+        authorize_member_change(&claims, &incoming_member.id, Some(&incoming_member.role), pg_connection)?;
+
+        // Step 3: Update the member if it has not been changed since the client read it.
+        update_member(&incoming_member, pg_connection)
+    }).await;
+
+    match result {
+        // Step 4: Fire the updated member with its new version.
+        Ok(updated_member) => {
+            let etag = version_etag(updated_member.version);
+            Ok(Json(SuccessResponse {
+                message: format!("Successfully updated the member."),
+                data: updated_member,
+            }).customize().insert_header(etag))
+        }
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn remove_member_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
     member_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+    let member_id = member_id.into_inner();
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: Check that the authenticated user can remove the member from its team.
+        authorize_member_change(&claims, &member_id, None, pg_connection)?;
+
+        // Step 2: Delete the member from the database.
+        delete_member_by_id(&member_id, pg_connection).map(|_| true)
+    }).await;
 
-    // Step 2: Delete the member from the database.
-    if !delete_member_by_id(&member_id.into_inner(), &pg_connection) {
-        Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes()))
-    } else {
+    match result {
         // Step 3: Fire the response.
-        Ok(Json(SuccessResponse {
+        Ok(_) => Ok(Json(SuccessResponse {
             message: format!("Successfully deleted the member."),
             data: true,
-        }))
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
-pub(crate) async fn remove_all_members_api(
+pub(crate) async fn restore_member_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    member_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+) -> Result<Json<SuccessResponse<Member>>, ServerErrorResponse> {
+    let member_id = member_id.into_inner();
+    let result = execute(pool, move |pg_connection| {
+        retry_transaction(pg_connection, || {
+            // Step 1: Restore the member, the deleted member is not visible to the authorization.
+            let restored_member = restore_member_by_id(&member_id, pg_connection)?;
 
-    // Step 2: Delete all members from the database.
-    if delete_all_members(&pg_connection).is_ok() {
-        // Step 3: Fire the response.
-        Ok(Json(SuccessResponse {
-            message: format!("Successfully deleted all members."),
-            data: true,
-        }))
-    } else {
-        Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes()))
+            // Step 2: Check that the authenticated user can manage the member, otherwise the restoration is rolled back.
+            authorize_member_change(&claims, &member_id, None, pg_connection)?;
+            Ok(restored_member)
+        })
+    }).await;
+
+    match result {
+        // Step 3: Fire the restored member.
+        Ok(restored_member) => Ok(Json(SuccessResponse {
+            message: format!("Successfully restored the member."),
+            data: restored_member,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
@@ -211,17 +267,14 @@ pub(crate) async fn filter_members_by_name_api(
     other_name: Json<MemberName>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Vec<Member>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Filter members by name.
-    match filter_members_by_name(&other_name.name, &pg_connection) {
-        // Step 3: Fire the response.
+    // Step 1: Filter members by name.
+    match execute_read(pool, move |pg_connection| filter_members_by_name(&other_name.name, pg_connection)).await {
+        // Step 2: Fire the response.
         Ok(filtered_members) => Ok(Json(SuccessResponse {
             message: format!("Successfully retrieved the filtered members."),
             data: filtered_members,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
@@ -229,17 +282,14 @@ pub(crate) async fn get_all_member_names_related_to_team_api(
     team_id: Path<Uuid>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Vec<Name>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Filter member names related to the required team.
-    match get_all_member_names_by_team_id(&team_id.into_inner(), &pg_connection) {
-        // Step 3: Fire the response.
+    // Step 1: Filter member names related to the required team.
+    let team_id = team_id.into_inner();
+    match execute_read(pool, move |pg_connection| get_all_member_names_by_team_id(&team_id, pg_connection)).await {
+        // Step 2: Fire the response.
         Ok(member_names) => Ok(Json(SuccessResponse {
             message: format!("Successfully retrieved all member names."),
             data: member_names,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
-
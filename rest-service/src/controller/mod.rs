@@ -3,62 +3,98 @@ use actix_web::web::ServiceConfig;
 use dotenv::dotenv;
 use tracing_subscriber::EnvFilter;
 
+use crate::controller::auth_controller::{login_api, logout_api, refresh_api};
+use crate::controller::auth_guard::require_authentication;
 use crate::controller::auth_user_controller::{
-    find_auth_user_by_id_api, insert_auth_user_api, list_auth_users_api,
-    remove_all_auth_users_api, remove_auth_user_api,
+    find_auth_user_by_id_api, list_auth_users_api, remove_auth_user_api,
 };
 use crate::controller::member_controller::{
-    filter_members_by_name_api, find_member_email_api, find_member_info_api,
+    filter_members_by_name_api, find_member_by_id_api, find_member_email_api, find_member_info_api,
     get_all_member_names_related_to_team_api, insert_bulk_members_api, insert_member_api,
-    list_members_api, remove_all_members_api, remove_member_api,
+    list_members_api, remove_member_api, restore_member_api, update_member_api,
 };
+use crate::controller::search_controller::search_api;
 use crate::controller::team_controller::{
     find_team_by_id_api, insert_bulk_teams_api, insert_team_api, list_teams_api,
-    remove_all_teams_api, remove_team_api,
+    remove_team_api, replace_team_api, restore_team_api, update_team_api,
+};
+use crate::controller::user_controller::{
+    find_user_by_id_api, insert_bulk_users_api, insert_user_api, list_users_api, register_user_api,
+    remove_user_api, restore_user_api, update_user_api,
 };
-use crate::controller::user_controller::{insert_user_api, list_users_api};
 
+pub(crate) mod auth_controller;
+pub(crate) mod auth_guard;
 pub(crate) mod auth_user_controller;
+pub(crate) mod etag;
+pub(crate) mod list_query;
 pub(crate) mod member_controller;
+pub(crate) mod problem_details;
+pub(crate) mod search_controller;
 pub(crate) mod team_controller;
 pub(crate) mod user_controller;
+pub(crate) mod validated_json;
 
 pub fn routes(config: &mut ServiceConfig) {
     config
         .route("/health", web::get().to(|| async { HttpResponse::Ok().json("Hello World!!") }))
+        .service(
+            web::scope("/auth")
+                .route("/login", web::post().to(login_api))
+                .route("/refresh", web::post().to(refresh_api))
+                .route("/logout", web::post().to(logout_api)),
+        )
         .service(
             web::scope("/auth_user")
+                .wrap_fn(require_authentication)
                 .route("/list_paginated", web::get().to(list_auth_users_api))
-                .route("/insert", web::post().to(insert_auth_user_api))
                 .route("/remove/{auth_user_id}", web::delete().to(remove_auth_user_api))
-                .route("/remove_all", web::delete().to(remove_all_auth_users_api))
                 .route("/find/{auth_user_id}", web::get().to(find_auth_user_by_id_api)),
         )
         .service(
             web::scope("/member")
+                .wrap_fn(require_authentication)
                 .route("/find_email", web::get().to(find_member_email_api))
                 .route("/find_info", web::get().to(find_member_info_api))
+                .route("/find/{member_id}", web::get().to(find_member_by_id_api))
                 .route("/list_paginated", web::get().to(list_members_api))
                 .route("/insert", web::post().to(insert_member_api))
                 .route("/insert_bulk", web::post().to(insert_bulk_members_api))
+                .route("/update", web::put().to(update_member_api))
                 .route("/remove/{member_id}", web::delete().to(remove_member_api))
-                .route("/remove_all", web::delete().to(remove_all_members_api))
+                .route("/restore/{member_id}", web::post().to(restore_member_api))
                 .route("/filter_by_name", web::get().to(filter_members_by_name_api))
                 .route("/member_names_by_team_id/{team_id}", web::get().to(get_all_member_names_related_to_team_api)),
         )
         .service(
             web::scope("/team")
+                .wrap_fn(require_authentication)
                 .route("/list", web::get().to(list_teams_api))
                 .route("/insert", web::post().to(insert_team_api))
                 .route("/insert_bulk", web::post().to(insert_bulk_teams_api))
                 .route("/remove/{team_id}", web::delete().to(remove_team_api))
-                .route("/remove_all", web::delete().to(remove_all_teams_api))
-                .route("/find/{team_id}", web::get().to(find_team_by_id_api)),
+                .route("/restore/{team_id}", web::post().to(restore_team_api))
+                .route("/find/{team_id}", web::get().to(find_team_by_id_api))
+                .route("/{team_id}", web::put().to(replace_team_api))
+                .route("/{team_id}", web::patch().to(update_team_api)),
+        )
+        .service(
+            web::scope("/search")
+                .wrap_fn(require_authentication)
+                .route("", web::get().to(search_api)),
         )
+        // The registration is public, so it is registered before the authenticated "user" scope.
+        .route("/user/register", web::post().to(register_user_api))
         .service(
             web::scope("user")
+                .wrap_fn(require_authentication)
                 .route("/list", web::get().to(list_users_api))
                 .route("/insert", web::post().to(insert_user_api))
+                .route("/insert_bulk", web::post().to(insert_bulk_users_api))
+                .route("/update", web::put().to(update_user_api))
+                .route("/remove/{user_id}", web::delete().to(remove_user_api))
+                .route("/restore/{user_id}", web::post().to(restore_user_api))
+                .route("/find/{user_id}", web::get().to(find_user_by_id_api)),
         );
 }
 
@@ -0,0 +1,110 @@
+use std::future::Future;
+
+use actix_web::{Error, HttpRequest};
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+
+use error::error::{ErrorCode, ServerErrorResponse};
+
+// Rebuild the problem+json responses of the ServerErrorResponse errors with the request path as the "instance",
+// the error itself doesn't know the request that caused it.
+pub(crate) fn attach_problem_instance<S, B>(
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output=Result<ServiceResponse<BoxBody>, Error>>
+    where S: Service<ServiceRequest, Response=ServiceResponse<B>, Error=Error>,
+          B: MessageBody + 'static {
+    let response = srv.call(req);
+    async move {
+        let service_response = response.await?;
+        let problem_response = service_response
+            .response()
+            .error()
+            .and_then(|err| err.as_error::<ServerErrorResponse>())
+            .map(|err| err.problem_response(Some(service_response.request().path())));
+        match problem_response {
+            Some(problem_response) => Ok(service_response.into_response(problem_response)),
+            None => Ok(service_response.map_into_boxed_body()),
+        }
+    }
+}
+
+// The extractors reject the malformed payloads, query strings and paths with their own plain text response, they are
+// converted to ServerErrorResponse errors to be returned as problem details like the rest of the errors.
+pub(crate) fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> Error {
+    let code = match err {
+        JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => "payload-too-large-error",
+        JsonPayloadError::ContentType => "content-type-error",
+        JsonPayloadError::Deserialize(_) => "json-deserialize-error",
+        _ => "payload-error",
+    };
+    bad_request(code)
+}
+
+pub(crate) fn query_error_handler(_err: QueryPayloadError, _req: &HttpRequest) -> Error {
+    bad_request("query-deserialize-error")
+}
+
+pub(crate) fn path_error_handler(_err: PathError, _req: &HttpRequest) -> Error {
+    bad_request("path-deserialize-error")
+}
+
+fn bad_request(code: &str) -> Error {
+    ServerErrorResponse::BadReq(vec![ErrorCode::new(code)]).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, test, web};
+    use actix_web::web::{JsonConfig, PathConfig};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+    use validator::Validate;
+
+    use error::error::ProblemDetails;
+
+    use crate::controller::validated_json::{ValidatedJson, ValidatedJsonList};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Validate)]
+    struct Payload {
+        #[validate(length(min = 1, code = "name-length-error"))]
+        name: String,
+    }
+
+    async fn call_service(req: test::TestRequest) -> (u16, ProblemDetails) {
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(attach_problem_instance)
+                .app_data(JsonConfig::default().limit(64).error_handler(json_error_handler))
+                .app_data(PathConfig::default().error_handler(path_error_handler))
+                .route("/one", web::post().to(|_: ValidatedJson<Payload>| async { HttpResponse::Ok().finish() }))
+                .route("/list", web::post().to(|_: ValidatedJsonList<Payload>| async { HttpResponse::Ok().finish() }))
+                .route("/find/{id}", web::get().to(|_: web::Path<Uuid>| async { HttpResponse::Ok().finish() }))
+        ).await;
+        let response = test::call_service(&app, req.to_request()).await;
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+        (response.status().as_u16(), test::read_body_json(response).await)
+    }
+
+    #[actix_web::test]
+    async fn the_rejected_extractions_are_problem_details() {
+        let requests = [
+            (test::TestRequest::post().uri("/one").set_payload("{}").insert_header(("content-type", "application/json")), "json-deserialize-error"),
+            (test::TestRequest::post().uri("/list").set_payload("[{}]").insert_header(("content-type", "application/json")), "json-deserialize-error"),
+            (test::TestRequest::post().uri("/one").set_payload("{}").insert_header(("content-type", "text/plain")), "content-type-error"),
+            (test::TestRequest::post().uri("/one").set_json(Payload { name: "a".repeat(64) }), "payload-too-large-error"),
+            (test::TestRequest::get().uri("/find/not-a-uuid"), "path-deserialize-error"),
+        ];
+        for (req, code) in requests {
+            let (status, problem_details) = call_service(req).await;
+            assert_eq!((status, problem_details.detail.as_str()), (400, code));
+        }
+
+        let (status, problem_details) = call_service(test::TestRequest::post().uri("/list").set_json(vec![Payload { name: String::new() }])).await;
+        assert_eq!((status, problem_details.detail.as_str()), (422, "name-length-error"));
+        assert_eq!(problem_details.instance.as_deref(), Some("/list"));
+    }
+}
@@ -0,0 +1,28 @@
+use actix_web::web;
+use actix_web::web::{Json, Query};
+
+use error::error::ServerErrorResponse;
+use yugabyte::db_connection::CoreDBPool;
+use yugabyte::engine::execution::execute_read;
+use yugabyte::engine::search::search;
+use yugabyte::model::dto::SuccessResponse;
+use yugabyte::model::search::{SearchDTO, SearchResult};
+
+pub(crate) async fn search_api(
+    Query(search_dto): Query<SearchDTO>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<Json<SuccessResponse<Vec<SearchResult>>>, ServerErrorResponse> {
+    // Step 1: Search the members, the teams and the users ordered by their rank.
+    let result = execute_read(pool, move |pg_connection| {
+        search(&search_dto, pg_connection)
+    }).await;
+
+    match result {
+        // Step 2: Fire the ranked results.
+        Ok(search_results) => Ok(Json(SuccessResponse {
+            message: format!("Successfully found {} results.", search_results.len()),
+            data: search_results,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
@@ -1,75 +1,82 @@
-use actix_web::web;
+use actix_web::{CustomizeResponder, HttpRequest, Responder, web};
 use actix_web::web::{Json, Query};
 use uuid::Uuid;
 
-use error::error::{ErrorCodesWrapper, ServerErrorResponse};
-use yugabyte::db_connection::{CoreDBPool, pgdata_to_pgconnection};
-use yugabyte::engine::team::{count_teams, delete_all_teams, delete_team_by_id, find_team_by_id, insert_bulk_team, list_all_teams};
+use error::error::ServerErrorResponse;
+use yugabyte::db_connection::CoreDBPool;
+use yugabyte::engine::authorization::{authorize, authorize_team_restoration};
+use yugabyte::engine::execution::{execute, execute_read};
+use yugabyte::engine::retry::retry_transaction;
+use yugabyte::engine::team::{
+    count_teams, delete_team_by_id, find_team_by_id, insert_bulk_team, list_all_teams,
+    restore_team_by_id, update_team,
+};
 use yugabyte::model::dto::{PaginatedResponseDTO, PaginationDTO, SuccessResponse};
-use yugabyte::model::team::{NewTeam, Team};
+use yugabyte::model::role::Permission;
+use yugabyte::model::team::{NewTeam, Team, TeamChanges, TeamReplacement};
+
+use crate::controller::etag::{expected_version, version_etag};
+use crate::controller::list_query::ListQuery;
+use crate::controller::validated_json::{ValidatedJson, ValidatedJsonList};
+use crate::controller::auth_guard::AuthenticatedUser;
 
 pub(crate) async fn list_teams_api(
     Query(pagination_dto): Query<PaginationDTO>,
+    ListQuery(list_query_dto): ListQuery,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<PaginatedResponseDTO<Team>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Count all teams.
-    match count_teams(&pg_connection) {
-        Ok(teams_count) => {
-            // Step 3: List all paginated teams.
-            match list_all_teams(&pagination_dto, &pg_connection) {
-                Ok(paginated_list) => {
-                    let response = PaginatedResponseDTO {
-                        paginated_list,
-                        count: teams_count,
-                    };
-                    // Step 4: Fire the response.
-                    Ok(Json(SuccessResponse {
-                        message: format!("Successfully retrieved all teams."),
-                        data: response,
-                    }))
-                }
-                Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-            }
-        }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+    // Step 1: Count all teams and list the paginated teams.
+    let result = execute_read(pool, move |pg_connection| {
+        let teams_count = count_teams(&list_query_dto, pg_connection)?;
+        let page = list_all_teams(&pagination_dto, &list_query_dto, pg_connection)?;
+        Ok(PaginatedResponseDTO {
+            paginated_list: page.items,
+            count: teams_count,
+            next_cursor: page.next_cursor,
+        })
+    }).await;
+
+    match result {
+        // Step 2: Fire the response.
+        Ok(response) => Ok(Json(SuccessResponse {
+            message: format!("Successfully retrieved all teams."),
+            data: response,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn insert_team_api(
-    new_team: Json<NewTeam>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    new_team: ValidatedJson<NewTeam>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Team>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Insert the team into the database
-    match new_team.insert_team(&pg_connection) {
-        // Step 3: Fire the inserted team
+    // Step 1: Insert the team into the database with the authenticated user as its owner.
+    match execute(pool, move |pg_connection| new_team.insert_team(&claims, pg_connection)).await {
+        // Step 2: Fire the inserted team
         Ok(inserted_team) => Ok(Json(SuccessResponse {
             message: format!("Successfully added the new Team."),
             data: inserted_team,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn insert_bulk_teams_api(
-    new_teams: Json<Vec<NewTeam>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    new_teams: ValidatedJsonList<NewTeam>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<Vec<Team>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
     let mut teams = Vec::new();
 
-    // Step 2: Iterate over the New Teams and create the list of teams to be added in a bulk not to load the execution time of the database.
+    // Step 1: Iterate over the New Teams and create the list of teams to be added in a bulk not to load the execution time of the database.
     for new_team in new_teams.0 {
         let team = Team {
             id: Uuid::new_v4(),
             name: new_team.name,
             description: new_team.description,
+            deleted_at: None,
+            version: 1,
         };
         teams.push(team);
     }
@@ -85,70 +92,149 @@ pub(crate) async fn insert_bulk_teams_api(
         teams.clone()
     });*/
 
-    // Step 4: Insert the bulk of teams into the database.
-    match insert_bulk_team(&teams, &pg_connection) {
-        // Step 5: Fire the inserted teams.
+    // Step 2: Insert the bulk of teams into the database with the authenticated user as their owner.
+    match execute(pool, move |pg_connection| insert_bulk_team(&teams, &claims, pg_connection)).await {
+        // Step 3: Fire the inserted teams.
         Ok(inserted_teams) => Ok(Json(SuccessResponse {
             message: format!("Successfully added the bulk of Teams."),
             data: inserted_teams,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn replace_team_api(
+    req: HttpRequest,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    team_id: web::Path<Uuid>,
+    team_replacement: ValidatedJson<TeamReplacement>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<CustomizeResponder<Json<SuccessResponse<Team>>>, ServerErrorResponse> {
+    // Step 1: The version of the If-Match header is the expected version of the team.
+    let team_id = team_id.into_inner();
+    let mut team_replacement = team_replacement.0;
+    team_replacement.version = expected_version(&req, team_replacement.version)?;
+
+    let result = execute(pool, move |pg_connection| {
+        // Step 2: Only the team owner can replace the team.
+        authorize(&claims, &team_id, Permission::ManageTeam, pg_connection)?;
+
+        // Step 3: Replace all the fields of the team if it has not been changed since the client read it.
+        update_team(&team_replacement.into_update(team_id), pg_connection)
+    }).await;
+
+    match result {
+        // Step 4: Fire the replaced team with its new version.
+        Ok(replaced_team) => {
+            let etag = version_etag(replaced_team.version);
+            Ok(Json(SuccessResponse {
+                message: format!("Successfully replaced the team."),
+                data: replaced_team,
+            }).customize().insert_header(etag))
+        }
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn update_team_api(
+    req: HttpRequest,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    team_id: web::Path<Uuid>,
+    team_changes: ValidatedJson<TeamChanges>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<CustomizeResponder<Json<SuccessResponse<Team>>>, ServerErrorResponse> {
+    // Step 1: The version of the If-Match header is the expected version of the team.
+    let team_id = team_id.into_inner();
+    let mut team_changes = team_changes.0;
+    team_changes.version = expected_version(&req, team_changes.version)?;
+
+    let result = execute(pool, move |pg_connection| {
+        // Step 2: Only the team owner can update the team.
+        authorize(&claims, &team_id, Permission::ManageTeam, pg_connection)?;
+
+        // Step 3: Update the team if it has not been changed since the client read it.
+        let current_team = find_team_by_id(&team_id, pg_connection)?;
+        update_team(&team_changes.into_update(current_team), pg_connection)
+    }).await;
+
+    match result {
+        // Step 4: Fire the updated team with its new version.
+        Ok(updated_team) => {
+            let etag = version_etag(updated_team.version);
+            Ok(Json(SuccessResponse {
+                message: format!("Successfully updated the team."),
+                data: updated_team,
+            }).customize().insert_header(etag))
+        }
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn remove_team_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
     team_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+    let team_id = team_id.into_inner();
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: Only the team owner can delete the team.
+        authorize(&claims, &team_id, Permission::ManageTeam, pg_connection)?;
 
-    // Step 2: Delete the team from the database.
-    if !delete_team_by_id(&team_id.into_inner(), &pg_connection) {
-        Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes()))
-    } else {
+        // Step 2: Delete the team from the database.
+        delete_team_by_id(&team_id, pg_connection).map(|_| true)
+    }).await;
+
+    match result {
         // Step 3: Fire the response.
-        Ok(Json(SuccessResponse {
+        Ok(_) => Ok(Json(SuccessResponse {
             message: format!("Successfully deleted the team."),
             data: true,
-        }))
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
-pub(crate) async fn remove_all_teams_api(
+pub(crate) async fn restore_team_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    team_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
+) -> Result<Json<SuccessResponse<Team>>, ServerErrorResponse> {
+    let team_id = team_id.into_inner();
+    let result = execute(pool, move |pg_connection| {
+        retry_transaction(pg_connection, || {
+            // Step 1: Only the owner of the deleted team can restore it.
+            authorize_team_restoration(&claims, &team_id, pg_connection)?;
 
-    // Step 2: Delete all teams from the database.
-    if delete_all_teams(&pg_connection).is_ok() {
-        // Step 3: Fire the response.
-        Ok(Json(SuccessResponse {
-            message: format!("Successfully deleted all teams."),
-            data: true,
-        }))
-    } else {
-        Err(ServerErrorResponse::from(ErrorCodesWrapper::from("db-error").get_error_codes()))
+            // Step 2: Restore the team with its members.
+            restore_team_by_id(&team_id, pg_connection)
+        })
+    }).await;
+
+    match result {
+        // Step 3: Fire the restored team.
+        Ok(restored_team) => Ok(Json(SuccessResponse {
+            message: format!("Successfully restored the team."),
+            data: restored_team,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn find_team_by_id_api(
     team_id: web::Path<Uuid>,
     pool: web::Data<CoreDBPool>,
-) -> Result<Json<SuccessResponse<Team>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Find the team from the database.
-    match find_team_by_id(&team_id.into_inner(), &pg_connection) {
+) -> Result<CustomizeResponder<Json<SuccessResponse<Team>>>, ServerErrorResponse> {
+    // Step 1: Find the team from the database.
+    let team_id = team_id.into_inner();
+    match execute_read(pool, move |pg_connection| find_team_by_id(&team_id, pg_connection)).await {
         Ok(found_team) => {
-            // Step 3: Fire the response
+            // Step 2: Fire the response with the version of the team to be sent back as If-Match on its update.
+            let etag = version_etag(found_team.version);
             Ok(Json(SuccessResponse {
                 message: format!("Successfully found the Team."),
                 data: found_team,
-            }))
+            }).customize().insert_header(etag))
         }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
@@ -1,57 +1,195 @@
 use actix_web::web;
 use actix_web::web::{Json, Query};
+use uuid::Uuid;
 
-use error::error::{ErrorCodesWrapper, ServerErrorResponse};
-use yugabyte::db_connection::{CoreDBPool, pgdata_to_pgconnection};
-use yugabyte::engine::user::{count_users, list_all_users};
-use yugabyte::model::dto::{PaginatedResponseDTO, PaginationDTO, SuccessResponse};
-use yugabyte::model::user::{NewUser, User};
+use error::error::{Error, ServerErrorResponse};
+use yugabyte::db_connection::{AuthDBPool, CoreDBPool, pgdata_to_pgconnection};
+use yugabyte::engine::authorization::authorize_user_change;
+use yugabyte::engine::execution::{execute, execute_read};
+use yugabyte::engine::user::{
+    count_users, delete_user_by_id, find_user_by_id, list_all_users, register_bulk_users, restore_user_by_id,
+};
+use yugabyte::model::dto::{PaginatedResponseDTO, PaginationDTO, RegisteredUserDTO, SuccessResponse};
+use yugabyte::model::user::{NewUser, UpdateUser, User};
+
+use crate::controller::auth_guard::AuthenticatedUser;
+use crate::controller::list_query::ListQuery;
+use crate::controller::validated_json::{ValidatedJson, ValidatedJsonList};
 
 pub(crate) async fn list_users_api(
     Query(pagination_dto): Query<PaginationDTO>,
+    ListQuery(list_query_dto): ListQuery,
     pool: web::Data<CoreDBPool>,
 ) -> Result<Json<SuccessResponse<PaginatedResponseDTO<User>>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
-
-    // Step 2: Count all users.
-    match count_users(&pg_connection) {
-        Ok(users_count) => {
-            // Step 3: List all paginated users.
-            match list_all_users(&pagination_dto, &pg_connection) {
-                Ok(paginated_list) => {
-                    let response = PaginatedResponseDTO {
-                        paginated_list,
-                        count: users_count,
-                    };
-
-                    // Step 4: Fire the response.
-                    Ok(Json(SuccessResponse {
-                        message: format!("Successfully retrieved all users."),
-                        data: response,
-                    }))
-                }
-                Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
-            }
-        }
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+    // Step 1: Count all users and list the paginated users.
+    let result = execute_read(pool, move |pg_connection| {
+        let users_count = count_users(&list_query_dto, pg_connection)?;
+        let page = list_all_users(&pagination_dto, &list_query_dto, pg_connection)?;
+        Ok(PaginatedResponseDTO {
+            paginated_list: page.items,
+            count: users_count,
+            next_cursor: page.next_cursor,
+        })
+    }).await;
+
+    match result {
+        // Step 2: Fire the response.
+        Ok(response) => Ok(Json(SuccessResponse {
+            message: format!("Successfully retrieved all users."),
+            data: response,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
 }
 
 pub(crate) async fn insert_user_api(
-    new_user: Json<NewUser>,
+    new_user: ValidatedJson<NewUser>,
     pool: web::Data<CoreDBPool>,
+    auth_pool: web::Data<AuthDBPool>,
 ) -> Result<Json<SuccessResponse<User>>, ServerErrorResponse> {
-    // Step 1: Get the connection from pool data.
-    let pg_connection = pgdata_to_pgconnection(pool);
+    // Step 1: Insert the User into the core database and its AuthUser into the auth database
+    let result = execute(pool, move |pg_connection| {
+        let auth_pg_connection = pgdata_to_pgconnection(auth_pool)?;
+        new_user.register_user(pg_connection, &auth_pg_connection)
+    }).await;
 
-    // Step 2: Insert the User into the database
-    match new_user.add_user(&pg_connection) {
-        // Step 3: Fire the inserted user
-        Ok(inserted_user) => Ok(Json(SuccessResponse {
+    match result {
+        // Step 2: Fire the inserted user
+        Ok(registered_user) => Ok(Json(SuccessResponse {
             message: format!("Successfully added the new User."),
-            data: inserted_user,
+            data: registered_user.user,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn insert_bulk_users_api(
+    new_users: ValidatedJsonList<NewUser>,
+    pool: web::Data<CoreDBPool>,
+    auth_pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<Vec<User>>>, ServerErrorResponse> {
+    // Step 1: Insert the bulk of users into the core database and their auth users into the auth database.
+    let result = execute(pool, move |pg_connection| {
+        let auth_pg_connection = pgdata_to_pgconnection(auth_pool)?;
+        register_bulk_users(&new_users.0, pg_connection, &auth_pg_connection)
+    }).await;
+
+    match result {
+        // Step 2: Fire the inserted users.
+        Ok(registered_users) => Ok(Json(SuccessResponse {
+            message: format!("Successfully added the bulk of Users."),
+            data: registered_users.into_iter().map(|registered_user| registered_user.user).collect(),
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn register_user_api(
+    new_user: ValidatedJson<NewUser>,
+    pool: web::Data<CoreDBPool>,
+    auth_pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<RegisteredUserDTO>>, ServerErrorResponse> {
+    // Step 1: Create the User and its AuthUser with the same id.
+    let result = execute(pool, move |pg_connection| {
+        let auth_pg_connection = pgdata_to_pgconnection(auth_pool)?;
+        new_user.register_user(pg_connection, &auth_pg_connection)
+    }).await;
+
+    match result {
+        // Step 2: Fire the registered user
+        Ok(registered_user) => Ok(Json(SuccessResponse {
+            message: format!("Successfully registered the new User."),
+            data: registered_user,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn restore_user_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    user_id: web::Path<Uuid>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<Json<SuccessResponse<User>>, ServerErrorResponse> {
+    // Step 1: The users can restore themselves only, the auth user is kept while the user is deleted to be able to log in.
+    let user_id = user_id.into_inner();
+    if user_id != claims.sub {
+        return Err(ServerErrorResponse::from(Error::Forbidden("permission-denied".to_string())));
+    }
+
+    // Step 2: Restore the user with its memberships.
+    let result = execute(pool, move |pg_connection| restore_user_by_id(&user_id, pg_connection)).await;
+
+    match result {
+        // Step 3: Fire the restored user.
+        Ok(restored_user) => Ok(Json(SuccessResponse {
+            message: format!("Successfully restored the user."),
+            data: restored_user,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn find_user_by_id_api(
+    user_id: web::Path<Uuid>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<Json<SuccessResponse<User>>, ServerErrorResponse> {
+    // Step 1: Find the user from the database.
+    let user_id = user_id.into_inner();
+    match execute_read(pool, move |pg_connection| find_user_by_id(&user_id, pg_connection)).await {
+        // Step 2: Fire the response.
+        Ok(found_user) => Ok(Json(SuccessResponse {
+            message: format!("Successfully found the User."),
+            data: found_user,
         })),
-        Err(err) => Err(ServerErrorResponse::from(ErrorCodesWrapper::from(err).get_error_codes())),
+        Err(err) => Err(ServerErrorResponse::from(err)),
     }
-}
\ No newline at end of file
+}
+
+pub(crate) async fn update_user_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    update_user: ValidatedJson<UpdateUser>,
+    pool: web::Data<CoreDBPool>,
+    auth_pool: web::Data<AuthDBPool>,
+) -> Result<Json<SuccessResponse<User>>, ServerErrorResponse> {
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: The users can update themselves only.
+        authorize_user_change(&claims, &update_user.id, pg_connection)?;
+
+        // Step 2: Update the user, its auth user takes the new email as well.
+        let auth_pg_connection = pgdata_to_pgconnection(auth_pool)?;
+        update_user.update_user(pg_connection, &auth_pg_connection)
+    }).await;
+
+    match result {
+        // Step 3: Fire the updated user.
+        Ok(updated_user) => Ok(Json(SuccessResponse {
+            message: format!("Successfully updated the User."),
+            data: updated_user,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
+
+pub(crate) async fn remove_user_api(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    user_id: web::Path<Uuid>,
+    pool: web::Data<CoreDBPool>,
+) -> Result<Json<SuccessResponse<bool>>, ServerErrorResponse> {
+    let user_id = user_id.into_inner();
+    let result = execute(pool, move |pg_connection| {
+        // Step 1: The users can delete themselves only.
+        authorize_user_change(&claims, &user_id, pg_connection)?;
+
+        // Step 2: Delete the user with its memberships, its auth user is kept to be able to restore it.
+        delete_user_by_id(&user_id, pg_connection).map(|_| true)
+    }).await;
+
+    match result {
+        // Step 3: Fire the response.
+        Ok(_) => Ok(Json(SuccessResponse {
+            message: format!("Successfully deleted the user."),
+            data: true,
+        })),
+        Err(err) => Err(ServerErrorResponse::from(err)),
+    }
+}
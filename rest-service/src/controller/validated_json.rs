@@ -0,0 +1,58 @@
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+
+use actix_web::{FromRequest, HttpRequest};
+use actix_web::dev::Payload;
+use actix_web::web::Json;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use error::error::{Error, ServerErrorResponse};
+
+// The JSON payload after running its validator derives, the invalid payload is rejected with 422 and the collected codes.
+pub(crate) struct ValidatedJson<T>(pub T);
+
+// The same as ValidatedJson for the bulk endpoints, the field of every error is prefixed by the index of the invalid item.
+pub(crate) struct ValidatedJsonList<T>(pub Vec<T>);
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJson<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output=Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            // The malformed JSON is still rejected by the Json extractor itself, through the error handler of its JsonConfig.
+            let json = json.await?;
+            match json.validate() {
+                Ok(_) => Ok(ValidatedJson(json.into_inner())),
+                Err(err) => Err(ServerErrorResponse::from(Error::from(err)).into()),
+            }
+        })
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJsonList<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output=Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = Json::<Vec<T>>::from_request(req, payload);
+        Box::pin(async move {
+            let json = json.await?;
+            match Error::validate_list(&json) {
+                Ok(_) => Ok(ValidatedJsonList(json.into_inner())),
+                Err(err) => Err(ServerErrorResponse::from(err).into()),
+            }
+        })
+    }
+}
@@ -1,26 +1,43 @@
-use std::env;
+use std::{env, process};
 
 use actix_web::{App, HttpServer};
 use actix_web::middleware::Logger;
-use actix_web::web::{Data, JsonConfig};
+use actix_web::web::{Data, JsonConfig, PathConfig, QueryConfig};
 
-use yugabyte::db_connection::CoreDBPool;
+use yugabyte::db_connection::{AuthDBPool, CoreDBPool};
+use yugabyte::migration::run_migrations_if_enabled;
+use yugabyte::util::jwt::check_jwt_secret;
 
 use crate::controller::{routes, start_tracing};
+use crate::controller::problem_details::{
+    attach_problem_instance, json_error_handler, path_error_handler, query_error_handler,
+};
 
 mod controller;
 
+// The default of actix-web, the bulk insertions send all their records in one body.
+const DEFAULT_JSON_PAYLOAD_LIMIT_BYTES: usize = 2 * 1024 * 1024;
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     start_tracing();
-    let core_db_pool_data = Data::new(CoreDBPool::default());
-    dotenv::dotenv().expect("Failed to read .env file");
+    check_jwt_secret().unwrap_or_else(|err| exit_with_diagnostic(err));
+    let core_db_pool = CoreDBPool::try_default().unwrap_or_else(|err| exit_with_diagnostic(err));
+    let auth_db_pool = AuthDBPool::try_default().unwrap_or_else(|err| exit_with_diagnostic(err));
+    run_migrations_if_enabled(&core_db_pool, &auth_db_pool).unwrap_or_else(|err| exit_with_diagnostic(err));
+    let json_payload_limit = json_payload_limit().unwrap_or_else(|err| exit_with_diagnostic(err));
+    let core_db_pool_data = Data::new(core_db_pool);
+    let auth_db_pool_data = Data::new(auth_db_pool);
 
     HttpServer::new(move || {
         App::new()
+            .wrap_fn(attach_problem_instance)
             .wrap(Logger::default())
-            .app_data(Data::new(JsonConfig::default().limit(4096)))
+            .app_data(JsonConfig::default().limit(json_payload_limit).error_handler(json_error_handler))
+            .app_data(QueryConfig::default().error_handler(query_error_handler))
+            .app_data(PathConfig::default().error_handler(path_error_handler))
             .app_data(core_db_pool_data.clone())
+            .app_data(auth_db_pool_data.clone())
             .configure(routes)
     })
         .bind(format!("{}:{}", env::var("HOST").unwrap(), env::var("REST_PORT").unwrap()))
@@ -28,3 +45,20 @@ async fn main() -> std::io::Result<()> {
         .run()
         .await
 }
+
+// The missing variable keeps the default limit, an invalid one is reported instead of being ignored.
+fn json_payload_limit() -> Result<usize, String> {
+    match env::var("JSON_PAYLOAD_LIMIT_BYTES") {
+        Ok(value) => value
+            .trim()
+            .parse::<usize>()
+            .map_err(|err| format!("JSON_PAYLOAD_LIMIT_BYTES has an invalid value {:?}: {}", value, err)),
+        Err(_) => Ok(DEFAULT_JSON_PAYLOAD_LIMIT_BYTES),
+    }
+}
+
+// Stop the startup with a readable message instead of a panic backtrace.
+fn exit_with_diagnostic(diagnostic: String) -> ! {
+    eprintln!("Startup failed: {}", diagnostic);
+    process::exit(1)
+}
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Only the SHA-256 hash of the refresh token is stored, the raw token is given to the client once.
+CREATE TABLE refresh_token
+(
+    id           UUID PRIMARY KEY,
+    auth_user_id UUID           NOT NULL,
+    token_hash   VARCHAR UNIQUE NOT NULL,
+    created_at   TIMESTAMP      NOT NULL,
+    expires_at   TIMESTAMP      NOT NULL,
+    revoked_at   TIMESTAMP,
+
+    CONSTRAINT fk_auth_user
+        FOREIGN KEY (auth_user_id)
+            REFERENCES auth_user (id)
+            ON DELETE CASCADE
+)
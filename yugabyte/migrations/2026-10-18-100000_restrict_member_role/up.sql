@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- The role is mapped to the Role enum, so the free-form values are normalized before restricting them.
+UPDATE member
+SET role = lower(role);
+
+UPDATE member
+SET role = 'member'
+WHERE role NOT IN ('owner', 'admin', 'member', 'viewer');
+
+ALTER TABLE member
+    ADD CONSTRAINT member_role_check CHECK (role IN ('owner', 'admin', 'member', 'viewer'));
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX member_team_id_idx;
\ No newline at end of file
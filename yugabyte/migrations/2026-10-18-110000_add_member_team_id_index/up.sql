@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX member_team_id_idx ON member (team_id);
\ No newline at end of file
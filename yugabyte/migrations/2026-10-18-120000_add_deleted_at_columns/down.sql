@@ -0,0 +1,21 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE member
+    DROP CONSTRAINT fk_team,
+    ADD CONSTRAINT fk_team
+        FOREIGN KEY (team_id)
+            REFERENCES team (id)
+            ON DELETE CASCADE,
+    DROP CONSTRAINT fk_user,
+    ADD CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES "user" (id)
+            ON DELETE CASCADE;
+
+ALTER TABLE member
+    DROP COLUMN deleted_at;
+
+ALTER TABLE "user"
+    DROP COLUMN deleted_at;
+
+ALTER TABLE team
+    DROP COLUMN deleted_at;
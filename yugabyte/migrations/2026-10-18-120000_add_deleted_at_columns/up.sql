@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- The rows are soft deleted by setting deleted_at, the queries of the engine skip them unless they are restored.
+ALTER TABLE team
+    ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE "user"
+    ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE member
+    ADD COLUMN deleted_at TIMESTAMP;
+
+-- The memberships must not be wiped by a hard delete of their team or user anymore.
+ALTER TABLE member
+    DROP CONSTRAINT fk_team,
+    ADD CONSTRAINT fk_team
+        FOREIGN KEY (team_id)
+            REFERENCES team (id)
+            ON DELETE RESTRICT,
+    DROP CONSTRAINT fk_user,
+    ADD CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES "user" (id)
+            ON DELETE RESTRICT;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE member
+    DROP COLUMN version;
+
+ALTER TABLE team
+    DROP COLUMN version;
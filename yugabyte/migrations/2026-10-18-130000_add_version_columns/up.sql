@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- The version is increased by every update, the update is rejected when the expected version is not the current one.
+ALTER TABLE team
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE member
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS team_name_trgm_idx;
+DROP INDEX IF EXISTS user_email_trgm_idx;
+DROP INDEX IF EXISTS user_name_trgm_idx;
+DROP INDEX IF EXISTS member_name_trgm_idx;
+
+DROP INDEX team_search_idx;
+DROP INDEX user_search_idx;
+DROP INDEX member_name_search_idx;
+
+DROP FUNCTION search_headline(TEXT, tsquery);
+DROP FUNCTION search_query(TEXT);
+DROP FUNCTION search_vector_pair(TEXT, TEXT);
+DROP FUNCTION search_vector(TEXT);
@@ -0,0 +1,49 @@
+-- Your SQL goes here
+-- YugabyteDB follows PostgreSQL 11 that has no generated columns, so the tsvector of the searchable columns is indexed as an
+-- expression. The expressions are wrapped in immutable functions to be indexable and to be used as they are by the queries.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE FUNCTION search_vector(document TEXT) RETURNS tsvector
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT to_tsvector('simple', coalesce(document, ''))
+$$;
+
+CREATE FUNCTION search_vector_pair(first_document TEXT, second_document TEXT) RETURNS tsvector
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT to_tsvector('simple', coalesce(first_document, '') || ' ' || coalesce(second_document, ''))
+$$;
+
+CREATE FUNCTION search_query(query TEXT) RETURNS tsquery
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT websearch_to_tsquery('simple', query)
+$$;
+
+-- The matched words of the document are wrapped in <mark> tags.
+CREATE FUNCTION search_headline(document TEXT, query tsquery) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT ts_headline('simple', document, query, 'StartSel=<mark>, StopSel=</mark>, HighlightAll=true')
+$$;
+
+CREATE INDEX member_name_search_idx ON member USING gin (search_vector(name));
+CREATE INDEX user_search_idx ON "user" USING gin (search_vector_pair(name, email));
+CREATE INDEX team_search_idx ON team USING gin (search_vector_pair(name, description));
+
+-- The trigram indexes serve the fuzzy matches of the misspelled names. The ybgin access method of YugabyteDB has no
+-- gin_trgm_ops operator class, so the indexes are only created where it is supported. Without them the % matches of the
+-- search still work, they scan the rows that are not deleted instead of using an index.
+DO
+$$
+BEGIN
+    CREATE INDEX member_name_trgm_idx ON member USING gin (name gin_trgm_ops);
+    CREATE INDEX user_name_trgm_idx ON "user" USING gin (name gin_trgm_ops);
+    CREATE INDEX user_email_trgm_idx ON "user" USING gin (email gin_trgm_ops);
+    CREATE INDEX team_name_trgm_idx ON team USING gin (name gin_trgm_ops);
+EXCEPTION
+    WHEN OTHERS THEN
+        RAISE NOTICE 'The trigram indexes are not created: %', SQLERRM;
+END
+$$;
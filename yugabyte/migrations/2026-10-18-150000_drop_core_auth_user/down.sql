@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE auth_user
+(
+    id       UUID PRIMARY KEY,
+    email    VARCHAR UNIQUE NOT NULL,
+    password VARCHAR        NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- The credentials are kept in the auth database (auth_migrations), the copy created by the first migrations of the core
+-- database is dropped. The rows left in it must be moved to the auth database first, so the migration fails instead of
+-- dropping them.
+DO
+$$
+    BEGIN
+        IF EXISTS(SELECT 1 FROM auth_user) THEN
+            RAISE EXCEPTION 'The auth_user table of the core database is not empty, move its rows to the auth database before dropping it';
+        END IF;
+    END
+$$;
+
+DROP TABLE auth_user;
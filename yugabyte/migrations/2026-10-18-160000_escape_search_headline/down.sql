@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+CREATE OR REPLACE FUNCTION search_headline(document TEXT, query tsquery) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT ts_headline('simple', document, query, 'StartSel=<mark>, StopSel=</mark>, HighlightAll=true')
+$$;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- The names, the emails and the descriptions are written by the users, so the document is escaped before its matched words
+-- are wrapped in <mark> tags. The highlight is safe to render as HTML, the escaped characters are kept as entities by the parser.
+CREATE OR REPLACE FUNCTION search_headline(document TEXT, query tsquery) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE AS
+$$
+SELECT ts_headline(
+    'simple',
+    replace(replace(replace(replace(replace(document, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '"', '&quot;'), '''', '&#39;'),
+    query,
+    'StartSel=<mark>, StopSel=</mark>, HighlightAll=true'
+)
+$$;
@@ -1,8 +1,143 @@
-use crate::db_connection::PgPool;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use uuid::Uuid;
+
+use error::error::Error;
+
+use crate::db_connection::{PgPool, PgPooledConnection, read_connection};
+use crate::engine::team::find_teams_by_ids;
+use crate::engine::user::find_users_by_ids;
+use crate::model::member::Member;
+use crate::model::team::Team;
+use crate::model::user::User;
+use crate::util::jwt::Claims;
 
 pub struct GraphQLContext {
     pub pool: PgPool,
+    // The read replicas and follower read pools of the core database, it is used by the query resolvers.
+    pub read_pools: Vec<PgPool>,
+    // The pool of the auth database, it is used by the auth_user resolvers only.
+    pub auth_pool: PgPool,
+    // The claims of the access token sent in the "Authorization: Bearer" header, if any.
+    pub claims: Option<Claims>,
+    // The teams and the users of the members resolved by the request, it lives as long as the request.
+    pub loader: RelationLoader,
+}
+
+impl GraphQLContext {
+    pub fn authenticated_claims(&self) -> Result<&Claims, Error> {
+        self.claims
+            .as_ref()
+            .ok_or_else(|| Error::Unauthorized("missing-access-token".to_string()))
+    }
+
+    pub fn read_connection(&self) -> Result<PgPooledConnection, Error> {
+        read_connection(&self.pool, &self.read_pools)
+    }
+
+    // Collect the teams and the users of the listed members, they are loaded together by the first Member.team or Member.user.
+    pub fn prime_members<'a, I>(&self, members: I)
+        where I: IntoIterator<Item = &'a Member>, I::IntoIter: Clone {
+        let members = members.into_iter();
+        self.loader.teams.prime(members.clone().map(|member| member.team_id));
+        self.loader.users.prime(members.map(|member| member.user_id));
+    }
+
+    pub fn load_team(&self, team_id: &Uuid) -> Result<Team, Error> {
+        self.loader.teams
+            .load(team_id, |team| team.id, |team_ids| find_teams_by_ids(team_ids, &*self.read_connection()?))?
+            .ok_or_else(|| Error::NotFound("team-not-found".to_string()))
+    }
+
+    pub fn load_user(&self, user_id: &Uuid) -> Result<User, Error> {
+        self.loader.users
+            .load(user_id, |user| user.id, |user_ids| find_users_by_ids(user_ids, &*self.read_connection()?))?
+            .ok_or_else(|| Error::NotFound("user-not-found".to_string()))
+    }
 }
 
 // This impl allows us to pass in GraphQLContext as the Context for GraphQL objects.
 impl juniper::Context for GraphQLContext {}
+
+#[derive(Default)]
+pub struct RelationLoader {
+    teams: BatchLoader<Team>,
+    users: BatchLoader<User>,
+}
+
+// The records are looked up by their ids with one query for all the ids primed since the previous query, the found records
+// and the missing ids are kept until the end of the request.
+pub struct BatchLoader<T> {
+    state: Mutex<BatchState<T>>,
+}
+
+struct BatchState<T> {
+    pending_ids: Vec<Uuid>,
+    loaded: HashMap<Uuid, Option<T>>,
+}
+
+impl<T> Default for BatchLoader<T> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(BatchState {
+                pending_ids: Vec::new(),
+                loaded: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<T: Clone> BatchLoader<T> {
+    pub fn prime<I: IntoIterator<Item = Uuid>>(&self, ids: I) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let new_ids: Vec<Uuid> = ids.into_iter().filter(|id| !state.loaded.contains_key(id)).collect();
+        state.pending_ids.extend(new_ids);
+    }
+
+    pub fn load<K, F>(&self, id: &Uuid, key: K, fetch: F) -> Result<Option<T>, Error>
+        where K: Fn(&T) -> Uuid, F: FnOnce(&[Uuid]) -> Result<Vec<T>, Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !state.loaded.contains_key(id) {
+            let mut ids = std::mem::take(&mut state.pending_ids);
+            ids.push(*id);
+            ids.sort();
+            ids.dedup();
+            ids.retain(|pending_id| !state.loaded.contains_key(pending_id));
+            let records = fetch(&ids)?;
+            state.loaded.extend(ids.into_iter().map(|missing_id| (missing_id, None)));
+            state.loaded.extend(records.into_iter().map(|record| (key(&record), Some(record))));
+        }
+        Ok(state.loaded.get(id).cloned().flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn the_primed_ids_are_loaded_with_one_query() {
+        let loader: BatchLoader<Uuid> = BatchLoader::default();
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let missing_id = Uuid::new_v4();
+        let queries: RefCell<Vec<Vec<Uuid>>> = RefCell::new(Vec::new());
+        let fetch = |requested_ids: &[Uuid]| {
+            queries.borrow_mut().push(requested_ids.to_vec());
+            Ok(requested_ids.iter().filter(|id| **id != missing_id).copied().collect())
+        };
+
+        loader.prime(ids.iter().copied().chain(ids.iter().copied()));
+        for id in &ids {
+            assert_eq!(loader.load(id, |record| *record, fetch).unwrap(), Some(*id));
+        }
+        assert_eq!(loader.load(&missing_id, |record| *record, fetch).unwrap(), None);
+        assert_eq!(loader.load(&missing_id, |record| *record, fetch).unwrap(), None);
+
+        let mut first_query = ids.clone();
+        first_query.sort();
+        assert_eq!(*queries.borrow(), vec![first_query, vec![missing_id]]);
+    }
+}
@@ -5,73 +5,120 @@ use uuid::Uuid;
 
 use error::error::Error;
 
+use crate::engine::pagination::{into_page, page_limit, page_start};
+use crate::engine::retry::retry_transaction;
 use crate::model::auth_user::AuthUser;
-use crate::model::dto::PaginationDTO;
+use crate::model::dto::{Page, PaginationDTO};
 use crate::model::user::NewUser;
-use crate::schema::auth_user::dsl::auth_user;
+use crate::schema::auth_user::dsl::{auth_user, email, password};
 use crate::schema::auth_user::dsl::id as auth_user_id;
+use crate::util::password::{hash_password, needs_rehash, verify_dummy_password, verify_password};
 
 impl NewUser {
-    pub fn add_auth_user(&self, connection: &PgConnection) -> Result<AuthUser, Error> {
-        // todo: don't forget to hash the password
+    // The auth_user takes the same id of its user when both of them are created together.
+    pub fn add_auth_user_with_id(&self, other_auth_user_id: &Uuid, connection: &PgConnection) -> Result<AuthUser, Error> {
         let initialized_auth_user = AuthUser {
-            id: Uuid::new_v4(),
+            id: *other_auth_user_id,
             email: self.email.clone(),
-            password: self.password.clone(),
+            password: hash_password(&self.password)?,
         };
-        diesel::insert_into(auth_user)
-            .values(&initialized_auth_user)
-            .get_result(connection)
-            .map_err(|err| Error::from(err))
+        retry_transaction(connection, || {
+            diesel::insert_into(auth_user)
+                .values(&initialized_auth_user)
+                .get_result(connection)
+                .map_err(Error::from)
+        })
     }
 }
 
+// The passwords of the incoming auth_users are plain, they will be hashed before the insertion.
 pub fn insert_bulk_auth_users(
     other_auth_users: &Vec<AuthUser>,
     connection: &PgConnection,
 ) -> Result<Vec<AuthUser>, Error> {
-    diesel::insert_into(auth_user)
-        .values(other_auth_users)
-        .get_results::<AuthUser>(connection)
-        .map_err(|err| Error::from(err))
+    let mut hashed_auth_users = Vec::new();
+    for other_auth_user in other_auth_users {
+        hashed_auth_users.push(AuthUser {
+            id: other_auth_user.id,
+            email: other_auth_user.email.clone(),
+            password: hash_password(&other_auth_user.password)?,
+        });
+    }
+    insert_bulk_hashed_auth_users(&hashed_auth_users, connection)
+}
+
+// Insert auth_users whose passwords are already hashed, e.g. re-inserting auth_users that were loaded from the database.
+pub fn insert_bulk_hashed_auth_users(
+    other_auth_users: &Vec<AuthUser>,
+    connection: &PgConnection,
+) -> Result<Vec<AuthUser>, Error> {
+    retry_transaction(connection, || {
+        diesel::insert_into(auth_user)
+            .values(other_auth_users)
+            .get_results::<AuthUser>(connection)
+            .map_err(Error::from)
+    })
 }
 
 pub fn list_all_auth_users(
     pagination_dto: &PaginationDTO,
     connection: &PgConnection,
-) -> Result<Vec<AuthUser>, Error> {
-    auth_user
-        .limit(pagination_dto.page_size as i64)
-        .offset(pagination_dto.offset as i64)
+) -> Result<Page<AuthUser>, Error> {
+    let mut query = auth_user
+        .order(auth_user_id.asc())
+        .limit(page_limit(pagination_dto))
+        .into_boxed();
+    query = match page_start(pagination_dto)? {
+        Some(last_id) => query.filter(auth_user_id.gt(last_id)),
+        None => query.offset(pagination_dto.offset as i64),
+    };
+    let auth_users = query
         .load::<AuthUser>(connection)
-        .map_err(|err| Error::from(err))
+        .map_err(Error::from)?;
+    Ok(into_page(auth_users, pagination_dto, |other_auth_user| &other_auth_user.id))
 }
 
 pub fn count_auth_users(connection: &PgConnection) -> Result<i64, Error> {
     auth_user
         .count()
         .get_result(connection)
-        .map_err(|err| Error::from(err))
+        .map_err(Error::from)
 }
 
-// todo: need to return the deleted AuthUser to use it in the GraphQL
-pub fn delete_auth_user_by_id(other_auth_user_id: &Uuid, connection: &PgConnection) -> bool {
-    match diesel::delete(
-        auth_user.filter(auth_user_id.eq(other_auth_user_id))
-    )
-        .execute(connection)
-        .map_err(|err| Error::from(err)) {
-        // I can return the deleted object but I decided to return bool to show you that I can manage the code.
-        Ok(0) => false,
-        Ok(1) => true,
-        _ => false,
-    }
+// It returns the number of the deleted auth_users, the missing auth_user is not found.
+pub fn delete_auth_user_by_id(other_auth_user_id: &Uuid, connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        let deleted_auth_users = diesel::delete(auth_user.filter(auth_user_id.eq(other_auth_user_id)))
+            .execute(connection)
+            .map_err(Error::from)?;
+        match deleted_auth_users {
+            0 => Err(Error::NotFound("auth-user-not-found".to_string())),
+            _ => Ok(deleted_auth_users),
+        }
+    })
 }
 
-pub fn delete_all_auth_users(connection: &PgConnection) -> Result<Vec<AuthUser>, Error> {
-    diesel::delete(auth_user)
-        .get_results::<AuthUser>(connection)
-        .map_err(|err| Error::from(err))
+// Delete the auth_users of a bulk registration whose users were not committed, it returns the number of the deleted auth_users.
+pub fn delete_auth_users_by_ids(other_auth_user_ids: &[Uuid], connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        diesel::delete(auth_user.filter(auth_user_id.eq_any(other_auth_user_ids)))
+            .execute(connection)
+            .map_err(Error::from)
+    })
+}
+
+// The users that were inserted without an auth_user have nothing to update, so it returns the number of the updated auth_users.
+pub fn update_auth_user_email(
+    other_auth_user_id: &Uuid,
+    other_email: &String,
+    connection: &PgConnection,
+) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        diesel::update(auth_user.find(other_auth_user_id))
+            .set(email.eq(other_email))
+            .execute(connection)
+            .map_err(Error::from)
+    })
 }
 
 pub fn find_auth_user_by_id(
@@ -81,5 +128,53 @@ pub fn find_auth_user_by_id(
     auth_user
         .find(other_auth_user_id)
         .get_result::<AuthUser>(connection)
-        .map_err(|err| Error::from(err))
+        .map_err(Error::from)
+}
+
+pub fn find_auth_user_by_email(
+    other_email: &String,
+    connection: &PgConnection,
+) -> Result<AuthUser, Error> {
+    auth_user
+        .filter(email.eq(other_email))
+        .get_result::<AuthUser>(connection)
+        .map_err(Error::from)
+}
+
+// Returns the AuthUser if the candidate password matches the stored hash, None if the email is unknown or the password is wrong.
+// If the stored hash was computed with older cost parameters, it will be replaced by a new hash of the candidate password.
+pub fn verify_auth_user_password(
+    other_email: &String,
+    candidate: &str,
+    connection: &PgConnection,
+) -> Result<Option<AuthUser>, Error> {
+    let found_auth_user = match find_auth_user_by_email(other_email, connection) {
+        Ok(found_auth_user) => found_auth_user,
+        Err(Error::DBError(diesel::result::Error::NotFound)) => {
+            verify_dummy_password(candidate);
+            return Ok(None);
+        }
+        Err(err) => return Err(err),
+    };
+
+    if !verify_password(&found_auth_user.password, candidate) {
+        return Ok(None);
+    }
+
+    if needs_rehash(&found_auth_user.password) {
+        // The login should not fail because of the rehash, the old hash is still valid.
+        if let Ok(rehashed_password) = hash_password(candidate) {
+            let rehash = retry_transaction(connection, || {
+                diesel::update(auth_user.find(&found_auth_user.id))
+                    .set(password.eq(&rehashed_password))
+                    .get_result::<AuthUser>(connection)
+                    .map_err(Error::from)
+            });
+            if let Ok(rehashed_auth_user) = rehash {
+                return Ok(Some(rehashed_auth_user));
+            }
+        }
+    }
+
+    Ok(Some(found_auth_user))
 }
@@ -0,0 +1,122 @@
+use chrono::NaiveDateTime;
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl};
+use uuid::Uuid;
+
+use error::error::Error;
+
+use crate::engine::member::find_member_by_id;
+use crate::engine::user::find_user_by_id;
+use crate::model::member::Member;
+use crate::model::role::{Permission, Role};
+use crate::model::user::User;
+use crate::schema::member::dsl::{deleted_at, expired_at, member, role, team_id, user_id};
+use crate::schema::team::dsl as team_dsl;
+use crate::schema::user::dsl::user;
+use crate::schema::user::dsl::deleted_at as user_deleted_at;
+use crate::util::jwt::Claims;
+use crate::util::utils::current_timestamp;
+
+// Find the active role of the authenticated user inside the team. The subject of the access token is the id of the user, its
+// auth_user is registered with the same id.
+pub fn find_team_role(
+    claims: &Claims,
+    other_team_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<Option<Role>, Error> {
+    member
+        .inner_join(user)
+        .filter(user_id.eq(claims.sub))
+        .filter(user_deleted_at.is_null())
+        .filter(team_id.eq(other_team_id))
+        .filter(deleted_at.is_null())
+        .filter(expired_at.is_null().or(expired_at.gt(current_timestamp())))
+        .select(role)
+        .first::<Role>(connection)
+        .optional()
+        .map_err(Error::from)
+}
+
+// Returns the role of the authenticated user if it has the permission inside the team, the users without a role are rejected.
+pub fn authorize(
+    claims: &Claims,
+    other_team_id: &Uuid,
+    permission: Permission,
+    connection: &PgConnection,
+) -> Result<Role, Error> {
+    match find_team_role(claims, other_team_id, connection)? {
+        Some(caller_role) if caller_role.has_permission(permission) => Ok(caller_role),
+        _ => Err(Error::Forbidden("permission-denied".to_string())),
+    }
+}
+
+// The members deleted with the team share its deletion date, so the role of the authenticated user is looked up between them
+// to check the restoration of the team before restoring it.
+pub fn authorize_team_restoration(
+    claims: &Claims,
+    other_team_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<Role, Error> {
+    let deletion_date = team_dsl::team
+        .find(other_team_id)
+        .select(team_dsl::deleted_at)
+        .first::<Option<NaiveDateTime>>(connection)?
+        .ok_or_else(|| Error::NotFound("team-not-found".to_string()))?;
+    let caller_role = member
+        .inner_join(user)
+        .filter(user_id.eq(claims.sub))
+        .filter(user_deleted_at.is_null())
+        .filter(team_id.eq(other_team_id))
+        .filter(deleted_at.eq(deletion_date))
+        .filter(expired_at.is_null().or(expired_at.gt(current_timestamp())))
+        .select(role)
+        .first::<Role>(connection)
+        .optional()?;
+    match caller_role {
+        Some(caller_role) if caller_role.has_permission(Permission::ManageTeam) => Ok(caller_role),
+        _ => Err(Error::Forbidden("permission-denied".to_string())),
+    }
+}
+
+// Check that the authenticated user can add, change or remove a member holding the given role inside the team.
+pub fn authorize_member_role(
+    claims: &Claims,
+    other_team_id: &Uuid,
+    member_role: &Role,
+    connection: &PgConnection,
+) -> Result<(), Error> {
+    let caller_role = authorize(claims, other_team_id, Permission::ManageMembers, connection)?;
+    if caller_role.can_manage(member_role) {
+        Ok(())
+    } else {
+        Err(Error::Forbidden("permission-denied".to_string()))
+    }
+}
+
+// Load the member and check that the authenticated user can manage its current role and, if it changes, the new one.
+pub fn authorize_member_change(
+    claims: &Claims,
+    other_member_id: &Uuid,
+    new_role: Option<&Role>,
+    connection: &PgConnection,
+) -> Result<Member, Error> {
+    let found_member = find_member_by_id(other_member_id, connection)?;
+    authorize_member_role(claims, &found_member.team_id, &found_member.role, connection)?;
+    if let Some(new_role) = new_role {
+        authorize_member_role(claims, &found_member.team_id, new_role, connection)?;
+    }
+    Ok(found_member)
+}
+
+// Load the user and check that it is the authenticated user, the users can change or remove themselves only.
+pub fn authorize_user_change(
+    claims: &Claims,
+    other_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<User, Error> {
+    let found_user = find_user_by_id(other_user_id, connection)?;
+    if found_user.id == claims.sub {
+        Ok(found_user)
+    } else {
+        Err(Error::Forbidden("permission-denied".to_string()))
+    }
+}
@@ -0,0 +1,33 @@
+use actix_web::web;
+use diesel::PgConnection;
+
+use error::error::Error;
+
+use crate::db_connection::{CoreDBPool, DBPoolConvertable, pgdata_to_pgconnection};
+
+// Run the diesel work on the blocking thread pool, the diesel calls are synchronous and would stall the actix workers.
+// The connection is acquired on the blocking thread as well because waiting for a free connection blocks too.
+pub async fn execute<T, F, R>(pgdata: web::Data<T>, query: F) -> Result<R, Error>
+    where T: DBPoolConvertable + Send + Sync + 'static,
+          F: FnOnce(&PgConnection) -> Result<R, Error> + Send + 'static,
+          R: Send + 'static {
+    web::block(move || {
+        let pg_connection = pgdata_to_pgconnection(pgdata)?;
+        query(&pg_connection)
+    })
+        .await
+        .map_err(|_| Error::InternalServerError("blocking-execution-error".to_string()))?
+}
+
+// The same as execute for the read-only queries of the core database, they run on a read replica or a follower read
+// connection when one is configured and reachable.
+pub async fn execute_read<F, R>(pgdata: web::Data<CoreDBPool>, query: F) -> Result<R, Error>
+    where F: FnOnce(&PgConnection) -> Result<R, Error> + Send + 'static,
+          R: Send + 'static {
+    web::block(move || {
+        let pg_connection = pgdata.read_connection()?;
+        query(&pg_connection)
+    })
+        .await
+        .map_err(|_| Error::InternalServerError("blocking-execution-error".to_string()))?
+}
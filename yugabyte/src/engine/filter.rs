@@ -0,0 +1,429 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::{BoolExpressionMethods, BoxableExpression, ExpressionMethods, IntoSql, PgTextExpressionMethods, QueryDsl};
+use diesel::pg::Pg;
+use diesel::sql_types::Bool;
+use uuid::Uuid;
+
+use error::error::{Error, ErrorCode};
+
+use crate::engine::pagination::{Cursor, invalid_cursor};
+use crate::model::dto::{FilterDTO, FilterOperator, ListQueryDTO, SortDTO};
+use crate::model::member::Member;
+use crate::model::role::Role;
+use crate::model::team::Team;
+use crate::model::user::User;
+use crate::schema::member;
+use crate::schema::member::dsl as member_dsl;
+use crate::schema::team;
+use crate::schema::team::dsl as team_dsl;
+use crate::schema::user;
+use crate::schema::user::dsl as user_dsl;
+
+pub(crate) type MemberQuery = member::BoxedQuery<'static, Pg>;
+pub(crate) type TeamQuery = team::BoxedQuery<'static, Pg>;
+pub(crate) type UserQuery = user::BoxedQuery<'static, Pg>;
+
+// The conditions of the records after the cursor of a sorted page.
+pub(crate) type MemberCondition = Box<dyn BoxableExpression<member::table, Pg, SqlType = Bool>>;
+pub(crate) type TeamCondition = Box<dyn BoxableExpression<team::table, Pg, SqlType = Bool>>;
+pub(crate) type UserCondition = Box<dyn BoxableExpression<user::table, Pg, SqlType = Bool>>;
+
+// The timestamps of the cursors keep their microseconds.
+const CURSOR_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+// The ids and the enums are compared for equality only.
+macro_rules! filter_equality {
+    ($query:expr, $column:expr, $filter:expr, $value:expr) => {
+        match $filter.operator {
+            FilterOperator::Eq => $query.filter($column.eq($value)),
+            FilterOperator::Ne => $query.filter($column.ne($value)),
+            _ => return Err(filter_error("filter-operator-error", $filter)),
+        }
+    };
+}
+
+macro_rules! filter_ordering {
+    ($query:expr, $column:expr, $filter:expr, $value:expr) => {
+        match $filter.operator {
+            FilterOperator::Eq => $query.filter($column.eq($value)),
+            FilterOperator::Ne => $query.filter($column.ne($value)),
+            FilterOperator::Gt => $query.filter($column.gt($value)),
+            FilterOperator::Gte => $query.filter($column.ge($value)),
+            FilterOperator::Lt => $query.filter($column.lt($value)),
+            FilterOperator::Lte => $query.filter($column.le($value)),
+            FilterOperator::Contains => return Err(filter_error("filter-operator-error", $filter)),
+        }
+    };
+}
+
+// The null value of the nullable timestamps is matched with IS NULL and IS NOT NULL.
+macro_rules! filter_nullable_timestamp {
+    ($query:expr, $column:expr, $filter:expr) => {
+        match ($filter.operator, &$filter.value) {
+            (FilterOperator::Eq, None) => $query.filter($column.is_null()),
+            (FilterOperator::Ne, None) => $query.filter($column.is_not_null()),
+            (_, None) => return Err(filter_error("filter-operator-error", $filter)),
+            _ => filter_ordering!($query, $column, $filter, timestamp_value($filter)?),
+        }
+    };
+}
+
+macro_rules! filter_text {
+    ($query:expr, $column:expr, $filter:expr) => {
+        match $filter.operator {
+            FilterOperator::Contains => $query.filter($column.ilike(contains_pattern(text_value($filter)?))),
+            _ => filter_ordering!($query, $column, $filter, text_value($filter)?.to_string()),
+        }
+    };
+}
+
+macro_rules! order_by {
+    ($query:expr, $column:expr, $sort_dto:expr, $is_first:expr) => {
+        match ($is_first, $sort_dto.descending) {
+            (true, false) => $query.order_by($column.asc()),
+            (true, true) => $query.order_by($column.desc()),
+            (false, false) => $query.then_order_by($column.asc()),
+            (false, true) => $query.then_order_by($column.desc()),
+        }
+    };
+}
+
+// The condition of one sorted column of the cursor: the records with the same value, and the records after the value in the
+// order of the column.
+macro_rules! keyset {
+    ($column:expr, $sort_dto:expr, $value:expr) => {{
+        let value = $value;
+        match $sort_dto.descending {
+            false => (Box::new($column.eq(value.clone())), Box::new($column.gt(value))),
+            true => (Box::new($column.eq(value.clone())), Box::new($column.lt(value))),
+        }
+    }};
+}
+
+// The null values are sorted after the rest in the ascending order and before them in the descending order.
+macro_rules! nullable_keyset {
+    ($column:expr, $sort_dto:expr, $value:expr) => {
+        match ($value, $sort_dto.descending) {
+            (Some(value), false) => (Box::new($column.eq(value)), Box::new($column.gt(value).or($column.is_null()))),
+            (Some(value), true) => (Box::new($column.eq(value)), Box::new($column.lt(value))),
+            (None, false) => (Box::new($column.is_null()), Box::new(false.into_sql::<Bool>())),
+            (None, true) => (Box::new($column.is_null()), Box::new($column.is_not_null())),
+        }
+    };
+}
+
+// The filterable and sortable columns of the members, the deleted members are never listed.
+pub(crate) fn filter_members(list_query_dto: &ListQueryDTO) -> Result<MemberQuery, Error> {
+    let mut query = member_dsl::member.filter(member_dsl::deleted_at.is_null()).into_boxed();
+    for filter in &list_query_dto.filters {
+        query = match filter.field.as_str() {
+            "id" => filter_equality!(query, member_dsl::id, filter, uuid_value(filter)?),
+            "team_id" => filter_equality!(query, member_dsl::team_id, filter, uuid_value(filter)?),
+            "user_id" => filter_equality!(query, member_dsl::user_id, filter, uuid_value(filter)?),
+            "name" => filter_text!(query, member_dsl::name, filter),
+            "identity_num" => filter_text!(query, member_dsl::identity_num, filter),
+            "role" => filter_equality!(query, member_dsl::role, filter, role_value(filter)?),
+            "assigned_at" => filter_ordering!(query, member_dsl::assigned_at, filter, timestamp_value(filter)?),
+            "expired_at" => filter_nullable_timestamp!(query, member_dsl::expired_at, filter),
+            "modification_date" => filter_nullable_timestamp!(query, member_dsl::modification_date, filter),
+            _ => return Err(filter_error("filter-field-error", filter)),
+        };
+    }
+    Ok(query)
+}
+
+pub(crate) fn sort_members(mut query: MemberQuery, list_query_dto: &ListQueryDTO) -> Result<MemberQuery, Error> {
+    for (index, sort_dto) in list_query_dto.sort.iter().enumerate() {
+        let is_first = index == 0;
+        query = match sort_dto.field.as_str() {
+            "name" => order_by!(query, member_dsl::name, sort_dto, is_first),
+            "identity_num" => order_by!(query, member_dsl::identity_num, sort_dto, is_first),
+            "role" => order_by!(query, member_dsl::role, sort_dto, is_first),
+            "assigned_at" => order_by!(query, member_dsl::assigned_at, sort_dto, is_first),
+            "expired_at" => order_by!(query, member_dsl::expired_at, sort_dto, is_first),
+            "modification_date" => order_by!(query, member_dsl::modification_date, sort_dto, is_first),
+            _ => return Err(sort_error(sort_dto)),
+        };
+    }
+    Ok(match list_query_dto.sort.is_empty() {
+        true => query.order_by(member_dsl::id.asc()),
+        false => query.then_order_by(member_dsl::id.asc()),
+    })
+}
+
+// The members after the cursor in the order of sort_members, the columns are compared from the last sorted one to the first.
+pub(crate) fn members_after(cursor: &Cursor, list_query_dto: &ListQueryDTO) -> Result<MemberCondition, Error> {
+    let mut condition: MemberCondition = Box::new(member_dsl::id.gt(cursor.id));
+    for (sort_dto, value) in list_query_dto.sort.iter().zip(&cursor.sort_values).rev() {
+        let (equal, after): (MemberCondition, MemberCondition) = match sort_dto.field.as_str() {
+            "name" => keyset!(member_dsl::name, sort_dto, cursor_text(value)?),
+            "identity_num" => keyset!(member_dsl::identity_num, sort_dto, cursor_text(value)?),
+            "role" => keyset!(member_dsl::role, sort_dto, cursor_role(value)?),
+            "assigned_at" => keyset!(member_dsl::assigned_at, sort_dto, cursor_timestamp(value)?),
+            "expired_at" => nullable_keyset!(member_dsl::expired_at, sort_dto, cursor_nullable_timestamp(value)?),
+            "modification_date" => nullable_keyset!(member_dsl::modification_date, sort_dto, cursor_nullable_timestamp(value)?),
+            _ => return Err(sort_error(sort_dto)),
+        };
+        condition = Box::new(after.or(equal.and(condition)));
+    }
+    Ok(condition)
+}
+
+// The other fields are rejected by sort_members before the members are loaded.
+pub(crate) fn member_cursor(other_member: &Member, list_query_dto: &ListQueryDTO) -> Cursor {
+    let sort_values = list_query_dto.sort.iter().map(|sort_dto| match sort_dto.field.as_str() {
+        "name" => Some(other_member.name.clone()),
+        "identity_num" => Some(other_member.identity_num.clone()),
+        "role" => Some(other_member.role.as_str().to_string()),
+        "assigned_at" => Some(cursor_timestamp_text(&other_member.assigned_at)),
+        "expired_at" => other_member.expired_at.as_ref().map(cursor_timestamp_text),
+        "modification_date" => other_member.modification_date.as_ref().map(cursor_timestamp_text),
+        _ => None,
+    });
+    Cursor::sorted(other_member.id, list_query_dto, sort_values.collect())
+}
+
+pub(crate) fn filter_teams(list_query_dto: &ListQueryDTO) -> Result<TeamQuery, Error> {
+    let mut query = team_dsl::team.filter(team_dsl::deleted_at.is_null()).into_boxed();
+    for filter in &list_query_dto.filters {
+        query = match filter.field.as_str() {
+            "id" => filter_equality!(query, team_dsl::id, filter, uuid_value(filter)?),
+            "name" => filter_text!(query, team_dsl::name, filter),
+            "description" => filter_text!(query, team_dsl::description, filter),
+            _ => return Err(filter_error("filter-field-error", filter)),
+        };
+    }
+    Ok(query)
+}
+
+pub(crate) fn sort_teams(mut query: TeamQuery, list_query_dto: &ListQueryDTO) -> Result<TeamQuery, Error> {
+    for (index, sort_dto) in list_query_dto.sort.iter().enumerate() {
+        let is_first = index == 0;
+        query = match sort_dto.field.as_str() {
+            "name" => order_by!(query, team_dsl::name, sort_dto, is_first),
+            "description" => order_by!(query, team_dsl::description, sort_dto, is_first),
+            _ => return Err(sort_error(sort_dto)),
+        };
+    }
+    Ok(match list_query_dto.sort.is_empty() {
+        true => query.order_by(team_dsl::id.asc()),
+        false => query.then_order_by(team_dsl::id.asc()),
+    })
+}
+
+pub(crate) fn teams_after(cursor: &Cursor, list_query_dto: &ListQueryDTO) -> Result<TeamCondition, Error> {
+    let mut condition: TeamCondition = Box::new(team_dsl::id.gt(cursor.id));
+    for (sort_dto, value) in list_query_dto.sort.iter().zip(&cursor.sort_values).rev() {
+        let (equal, after): (TeamCondition, TeamCondition) = match sort_dto.field.as_str() {
+            "name" => keyset!(team_dsl::name, sort_dto, cursor_text(value)?),
+            "description" => keyset!(team_dsl::description, sort_dto, cursor_text(value)?),
+            _ => return Err(sort_error(sort_dto)),
+        };
+        condition = Box::new(after.or(equal.and(condition)));
+    }
+    Ok(condition)
+}
+
+pub(crate) fn team_cursor(other_team: &Team, list_query_dto: &ListQueryDTO) -> Cursor {
+    let sort_values = list_query_dto.sort.iter().map(|sort_dto| match sort_dto.field.as_str() {
+        "name" => Some(other_team.name.clone()),
+        "description" => Some(other_team.description.clone()),
+        _ => None,
+    });
+    Cursor::sorted(other_team.id, list_query_dto, sort_values.collect())
+}
+
+pub(crate) fn filter_users(list_query_dto: &ListQueryDTO) -> Result<UserQuery, Error> {
+    let mut query = user_dsl::user.filter(user_dsl::deleted_at.is_null()).into_boxed();
+    for filter in &list_query_dto.filters {
+        query = match filter.field.as_str() {
+            "id" => filter_equality!(query, user_dsl::id, filter, uuid_value(filter)?),
+            "email" => filter_text!(query, user_dsl::email, filter),
+            "name" => filter_text!(query, user_dsl::name, filter),
+            _ => return Err(filter_error("filter-field-error", filter)),
+        };
+    }
+    Ok(query)
+}
+
+pub(crate) fn sort_users(mut query: UserQuery, list_query_dto: &ListQueryDTO) -> Result<UserQuery, Error> {
+    for (index, sort_dto) in list_query_dto.sort.iter().enumerate() {
+        let is_first = index == 0;
+        query = match sort_dto.field.as_str() {
+            "email" => order_by!(query, user_dsl::email, sort_dto, is_first),
+            "name" => order_by!(query, user_dsl::name, sort_dto, is_first),
+            _ => return Err(sort_error(sort_dto)),
+        };
+    }
+    Ok(match list_query_dto.sort.is_empty() {
+        true => query.order_by(user_dsl::id.asc()),
+        false => query.then_order_by(user_dsl::id.asc()),
+    })
+}
+
+pub(crate) fn users_after(cursor: &Cursor, list_query_dto: &ListQueryDTO) -> Result<UserCondition, Error> {
+    let mut condition: UserCondition = Box::new(user_dsl::id.gt(cursor.id));
+    for (sort_dto, value) in list_query_dto.sort.iter().zip(&cursor.sort_values).rev() {
+        let (equal, after): (UserCondition, UserCondition) = match sort_dto.field.as_str() {
+            "email" => keyset!(user_dsl::email, sort_dto, cursor_text(value)?),
+            "name" => keyset!(user_dsl::name, sort_dto, cursor_text(value)?),
+            _ => return Err(sort_error(sort_dto)),
+        };
+        condition = Box::new(after.or(equal.and(condition)));
+    }
+    Ok(condition)
+}
+
+pub(crate) fn user_cursor(other_user: &User, list_query_dto: &ListQueryDTO) -> Cursor {
+    let sort_values = list_query_dto.sort.iter().map(|sort_dto| match sort_dto.field.as_str() {
+        "email" => Some(other_user.email.clone()),
+        "name" => Some(other_user.name.clone()),
+        _ => None,
+    });
+    Cursor::sorted(other_user.id, list_query_dto, sort_values.collect())
+}
+
+// The errors carry the invalid field to be reported like the validation errors of the payloads.
+fn filter_error(code: &str, filter: &FilterDTO) -> Error {
+    Error::ValidationError(vec![ErrorCode {
+        code: code.to_string(),
+        field: Some(filter.field.clone()),
+    }])
+}
+
+fn sort_error(sort_dto: &SortDTO) -> Error {
+    Error::ValidationError(vec![ErrorCode {
+        code: "sort-field-error".to_string(),
+        field: Some(sort_dto.field.clone()),
+    }])
+}
+
+fn text_value(filter: &FilterDTO) -> Result<&str, Error> {
+    filter
+        .value
+        .as_deref()
+        .ok_or_else(|| filter_error("filter-value-error", filter))
+}
+
+fn uuid_value(filter: &FilterDTO) -> Result<Uuid, Error> {
+    Uuid::parse_str(text_value(filter)?).map_err(|_| filter_error("filter-value-error", filter))
+}
+
+fn role_value(filter: &FilterDTO) -> Result<Role, Error> {
+    text_value(filter)?.parse::<Role>().map_err(|_| filter_error("filter-value-error", filter))
+}
+
+// The timestamps are written like "2022-02-28T04:54:32", or as a date like "2022-02-28" for its midnight.
+fn timestamp_value(filter: &FilterDTO) -> Result<NaiveDateTime, Error> {
+    let value = text_value(filter)?;
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_hms(0, 0, 0)))
+        .map_err(|_| filter_error("filter-value-error", filter))
+}
+
+// The wildcards of the value are escaped to match them literally.
+fn contains_pattern(value: &str) -> String {
+    let escaped_value = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped_value)
+}
+
+fn cursor_text(value: &Option<String>) -> Result<String, Error> {
+    value.clone().ok_or_else(invalid_cursor)
+}
+
+fn cursor_role(value: &Option<String>) -> Result<Role, Error> {
+    cursor_text(value)?.parse::<Role>().map_err(|_| invalid_cursor())
+}
+
+fn cursor_timestamp(value: &Option<String>) -> Result<NaiveDateTime, Error> {
+    NaiveDateTime::parse_from_str(&cursor_text(value)?, CURSOR_TIMESTAMP_FORMAT).map_err(|_| invalid_cursor())
+}
+
+fn cursor_nullable_timestamp(value: &Option<String>) -> Result<Option<NaiveDateTime>, Error> {
+    match value {
+        Some(_) => cursor_timestamp(value).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn cursor_timestamp_text(timestamp: &NaiveDateTime) -> String {
+    timestamp.format(CURSOR_TIMESTAMP_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::debug_query;
+
+    use super::*;
+
+    fn filter(field: &str, operator: FilterOperator, value: Option<&str>) -> FilterDTO {
+        FilterDTO {
+            field: field.to_string(),
+            operator,
+            value: value.map(|value| value.to_string()),
+        }
+    }
+
+    #[test]
+    fn filters_and_sort_are_bound_parameters() {
+        let list_query_dto = ListQueryDTO {
+            filters: vec![
+                filter("role", FilterOperator::Eq, Some("admin")),
+                filter("name", FilterOperator::Contains, Some("a%li")),
+                filter("expired_at", FilterOperator::Eq, None),
+                filter("assigned_at", FilterOperator::Gte, Some("2022-02-28")),
+            ],
+            sort: vec![SortDTO { field: "assigned_at".to_string(), descending: true }],
+        };
+        let query = sort_members(filter_members(&list_query_dto).unwrap(), &list_query_dto).unwrap();
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"member\".\"role\" = $1"), "{}", sql);
+        assert!(sql.contains("\"member\".\"name\" ILIKE $2"), "{}", sql);
+        assert!(sql.contains("\"member\".\"expired_at\" IS NULL"), "{}", sql);
+        assert!(sql.contains("ORDER BY \"member\".\"assigned_at\" DESC, \"member\".\"id\" ASC"), "{}", sql);
+        assert!(sql.contains("\"%a\\\\%li%\""), "{}", sql);
+    }
+
+    #[test]
+    fn the_sorted_cursor_continues_after_its_values() {
+        let list_query_dto = ListQueryDTO {
+            filters: vec![],
+            sort: vec![
+                SortDTO { field: "name".to_string(), descending: true },
+                SortDTO { field: "expired_at".to_string(), descending: false },
+            ],
+        };
+        let cursor = Cursor::sorted(Uuid::nil(), &list_query_dto, vec![Some("ali".to_string()), None]);
+        let query = filter_members(&ListQueryDTO::default()).unwrap().filter(members_after(&cursor, &list_query_dto).unwrap());
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(
+            sql.contains("(\"member\".\"name\" < $1 OR \"member\".\"name\" = $2 AND \
+                ($3 OR \"member\".\"expired_at\" IS NULL AND \"member\".\"id\" > $4))"),
+            "{}", sql
+        );
+
+        let invalid_cursor = Cursor::sorted(Uuid::nil(), &list_query_dto, vec![None, None]);
+        assert!(matches!(members_after(&invalid_cursor, &list_query_dto), Err(Error::BadRequest(_))));
+    }
+
+    #[test]
+    fn fields_outside_the_whitelist_are_rejected() {
+        let errors = [
+            filter_teams(&ListQueryDTO { filters: vec![filter("deleted_at", FilterOperator::Eq, None)], sort: vec![] }).err(),
+            filter_members(&ListQueryDTO { filters: vec![filter("role", FilterOperator::Gt, Some("admin"))], sort: vec![] }).err(),
+            filter_members(&ListQueryDTO { filters: vec![filter("assigned_at", FilterOperator::Eq, Some("yesterday"))], sort: vec![] }).err(),
+            sort_users(
+                filter_users(&ListQueryDTO::default()).unwrap(),
+                &ListQueryDTO { filters: vec![], sort: vec![SortDTO { field: "password".to_string(), descending: false }] },
+            ).err(),
+        ];
+        let codes: Vec<String> = errors
+            .into_iter()
+            .map(|err| match err {
+                Some(Error::ValidationError(error_codes)) => error_codes[0].code.clone(),
+                other => panic!("Unexpected result {:?}", other),
+            })
+            .collect();
+        assert_eq!(codes, ["filter-field-error", "filter-operator-error", "filter-value-error", "sort-field-error"]);
+    }
+}
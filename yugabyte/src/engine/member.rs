@@ -1,12 +1,23 @@
-use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use chrono::NaiveDateTime;
+use diesel::{ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl};
+use diesel::dsl::{exists, select};
 use uuid::Uuid;
 
 use error::error::Error;
 
-use crate::model::dto::PaginationDTO;
+use crate::engine::filter::{filter_members, member_cursor, MemberQuery, members_after, sort_members};
+use crate::engine::pagination::{into_sorted_page, page_limit, sorted_page_start};
+use crate::engine::retry::retry_transaction;
+use crate::model::dto::{ListQueryDTO, Page, PaginationDTO};
 use crate::model::member::{Member, Name, NewMember, UpdateMember};
-use crate::schema::member::dsl::{expired_at, identity_num, member, modification_date, name, role};
+use crate::model::role::Role;
+use crate::model::user::User;
+use crate::schema::member::all_columns as member_columns;
+use crate::schema::member::dsl::{deleted_at, expired_at, identity_num, member, modification_date, name, role, team_id, user_id, version};
 use crate::schema::member::dsl::id as member_id;
+use crate::schema::team::dsl as team_dsl;
+use crate::schema::user::dsl as user_dsl;
+use crate::util::jwt::Claims;
 use crate::util::utils::current_timestamp;
 
 impl NewMember {
@@ -17,15 +28,21 @@ impl NewMember {
             user_id: self.user_id,
             name: self.name.clone(),
             identity_num: self.identity_num.clone(),
-            role: self.role.clone(),
+            role: self.role,
             assigned_at: current_timestamp(),
             expired_at: self.expired_at,
             modification_date: None,
+            deleted_at: None,
+            version: 1,
         };
-        diesel::insert_into(member)
-            .values(initialized_member)
-            .get_result(connection)
-            .map_err(|_| Error::DuplicationError)
+        retry_transaction(connection, || {
+            check_active_team_and_user(&initialized_member.team_id, &initialized_member.user_id, connection)?;
+            check_deleted_duplication(&initialized_member.team_id, &initialized_member.user_id, connection)?;
+            diesel::insert_into(member)
+                .values(&initialized_member)
+                .get_result(connection)
+                .map_err(Error::from)
+        })
     }
 }
 
@@ -33,45 +50,225 @@ pub fn insert_bulk_members(
     other_members: &Vec<Member>,
     connection: &PgConnection,
 ) -> Result<Vec<Member>, Error> {
+    retry_transaction(connection, || {
+        for other_member in other_members {
+            check_active_team_and_user(&other_member.team_id, &other_member.user_id, connection)?;
+            check_deleted_duplication(&other_member.team_id, &other_member.user_id, connection)?;
+        }
+
+        diesel::insert_into(member)
+            .values(other_members)
+            .get_results::<Member>(connection)
+            .map_err(Error::from)
+    })
+}
+
+// The creator of the teams is added to them as their owner, so every team has someone to manage it. The creator has no
+// identity number yet, its user id stands for it until the member is updated.
+pub(crate) fn insert_team_owners(
+    other_team_ids: &[Uuid],
+    claims: &Claims,
+    connection: &PgConnection,
+) -> Result<Vec<Member>, Error> {
+    let creator = user_dsl::user
+        .filter(user_dsl::id.eq(claims.sub))
+        .filter(user_dsl::deleted_at.is_null())
+        .get_result::<User>(connection)
+        .optional()?
+        .ok_or_else(|| Error::Forbidden("permission-denied".to_string()))?;
+    let owners: Vec<Member> = other_team_ids
+        .iter()
+        .map(|other_team_id| Member {
+            id: Uuid::new_v4(),
+            team_id: *other_team_id,
+            user_id: creator.id,
+            name: creator.name.clone(),
+            identity_num: creator.id.to_string(),
+            role: Role::Owner,
+            assigned_at: current_timestamp(),
+            expired_at: None,
+            modification_date: None,
+            deleted_at: None,
+            version: 1,
+        })
+        .collect();
     diesel::insert_into(member)
-        .values(other_members)
+        .values(&owners)
         .get_results::<Member>(connection)
-        .map_err(|_| Error::DuplicationError)
+        .map_err(Error::from)
 }
 
 pub fn list_all_members(
     pagination_dto: &PaginationDTO,
+    list_query_dto: &ListQueryDTO,
     connection: &PgConnection,
-) -> Result<Vec<Member>, Error> {
-    member
-        .limit(pagination_dto.page_size as i64)
-        .offset(pagination_dto.offset as i64)
+) -> Result<Page<Member>, Error> {
+    load_members_page(filter_members(list_query_dto)?, pagination_dto, list_query_dto, connection)
+}
+
+// The members of the team are listed with the same filters, sort and pagination of the list of all members.
+pub fn list_team_members(
+    other_team_id: &Uuid,
+    pagination_dto: &PaginationDTO,
+    list_query_dto: &ListQueryDTO,
+    connection: &PgConnection,
+) -> Result<Page<Member>, Error> {
+    let team_members_query = filter_members(list_query_dto)?.filter(team_id.eq(*other_team_id));
+    load_members_page(team_members_query, pagination_dto, list_query_dto, connection)
+}
+
+fn load_members_page(
+    filtered_query: MemberQuery,
+    pagination_dto: &PaginationDTO,
+    list_query_dto: &ListQueryDTO,
+    connection: &PgConnection,
+) -> Result<Page<Member>, Error> {
+    let mut query = sort_members(filtered_query, list_query_dto)?
+        .limit(page_limit(pagination_dto));
+    query = match sorted_page_start(pagination_dto, list_query_dto)? {
+        Some(cursor) => query.filter(members_after(&cursor, list_query_dto)?),
+        None => query.offset(pagination_dto.offset as i64),
+    };
+    let members = query
         .load::<Member>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)?;
+    Ok(into_sorted_page(members, pagination_dto, |other_member| member_cursor(other_member, list_query_dto)))
 }
 
-pub fn count_members(connection: &PgConnection) -> Result<i64, Error> {
-    member
+pub fn count_members(list_query_dto: &ListQueryDTO, connection: &PgConnection) -> Result<i64, Error> {
+    filter_members(list_query_dto)?
         .count()
         .get_result(connection)
-        .map_err(|e| Error::DBError(e))
+        .map_err(Error::DBError)
 }
 
-pub fn delete_member_by_id(other_member_id: &Uuid, connection: &PgConnection) -> bool {
-    match diesel::delete(member.filter(member_id.eq(other_member_id)))
+// The missing or already deleted member is not found.
+pub fn delete_member_by_id(other_member_id: &Uuid, connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        let deleted_members = diesel::update(member.filter(member_id.eq(other_member_id)).filter(deleted_at.is_null()))
+            .set(deleted_at.eq(current_timestamp()))
+            .execute(connection)
+            .map_err(Error::DBError)?;
+        match deleted_members {
+            0 => Err(Error::NotFound("member-not-found".to_string())),
+            _ => Ok(deleted_members),
+        }
+    })
+}
+
+// Soft delete the members of the deleted team, the deletion date of the team is used to restore them with the team.
+pub(crate) fn delete_members_by_team_id(
+    other_team_id: &Uuid,
+    deletion_date: &NaiveDateTime,
+    connection: &PgConnection,
+) -> Result<usize, Error> {
+    diesel::update(member.filter(team_id.eq(other_team_id)).filter(deleted_at.is_null()))
+        .set(deleted_at.eq(deletion_date))
         .execute(connection)
-        .map_err(|e| Error::DBError(e)) {
-        Ok(0) => false,
-        Ok(1) => true,
-        _ => false,
-    }
+        .map_err(Error::DBError)
 }
 
-// todo: // todo: need to return the list of deleted items to use it in the GraphQL like what I did in the auth_user engine
-pub fn delete_all_members(connection: &PgConnection) -> Result<usize, Error> {
-    diesel::delete(member)
+pub(crate) fn delete_members_by_user_id(
+    other_user_id: &Uuid,
+    deletion_date: &NaiveDateTime,
+    connection: &PgConnection,
+) -> Result<usize, Error> {
+    diesel::update(member.filter(user_id.eq(other_user_id)).filter(deleted_at.is_null()))
+        .set(deleted_at.eq(deletion_date))
         .execute(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)
+}
+
+// Restore the members deleted with their team, unless their user is still deleted.
+pub(crate) fn restore_members_by_team_id(
+    other_team_id: &Uuid,
+    deletion_date: &NaiveDateTime,
+    connection: &PgConnection,
+) -> Result<usize, Error> {
+    let active_user_ids = user_dsl::user.filter(user_dsl::deleted_at.is_null()).select(user_dsl::id);
+    diesel::update(
+        member
+            .filter(team_id.eq(other_team_id))
+            .filter(deleted_at.eq(deletion_date))
+            .filter(user_id.eq_any(active_user_ids))
+    )
+        .set(deleted_at.eq(None::<NaiveDateTime>))
+        .execute(connection)
+        .map_err(Error::DBError)
+}
+
+// Restore the members deleted with their user, unless their team is still deleted.
+pub(crate) fn restore_members_by_user_id(
+    other_user_id: &Uuid,
+    deletion_date: &NaiveDateTime,
+    connection: &PgConnection,
+) -> Result<usize, Error> {
+    let active_team_ids = team_dsl::team.filter(team_dsl::deleted_at.is_null()).select(team_dsl::id);
+    diesel::update(
+        member
+            .filter(user_id.eq(other_user_id))
+            .filter(deleted_at.eq(deletion_date))
+            .filter(team_id.eq_any(active_team_ids))
+    )
+        .set(deleted_at.eq(None::<NaiveDateTime>))
+        .execute(connection)
+        .map_err(Error::DBError)
+}
+
+pub fn restore_member_by_id(
+    other_member_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<Member, Error> {
+    retry_transaction(connection, || {
+        let restored_member = diesel::update(member.filter(member_id.eq(other_member_id)).filter(deleted_at.is_not_null()))
+            .set(deleted_at.eq(None::<NaiveDateTime>))
+            .get_result::<Member>(connection)
+            .map_err(Error::DBError)?;
+        check_active_team_and_user(&restored_member.team_id, &restored_member.user_id, connection)?;
+        Ok(restored_member)
+    })
+}
+
+// The foreign keys still accept a soft deleted team or user, so they are checked before adding or restoring a member.
+fn check_active_team_and_user(
+    other_team_id: &Uuid,
+    other_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<(), Error> {
+    let found_team_id = team_dsl::team
+        .find(other_team_id)
+        .filter(team_dsl::deleted_at.is_null())
+        .select(team_dsl::id)
+        .first::<Uuid>(connection)
+        .optional()?;
+    if found_team_id.is_none() {
+        return Err(Error::NotFound("team-not-found".to_string()));
+    }
+    let found_user_id = user_dsl::user
+        .find(other_user_id)
+        .filter(user_dsl::deleted_at.is_null())
+        .select(user_dsl::id)
+        .first::<Uuid>(connection)
+        .optional()?;
+    if found_user_id.is_none() {
+        return Err(Error::NotFound("user-not-found".to_string()));
+    }
+    Ok(())
+}
+
+// The soft deleted membership of the user in the team is kept for its restoration, so adding the user to the team again is
+// rejected until the old member is restored.
+fn check_deleted_duplication(other_team_id: &Uuid, other_user_id: &Uuid, connection: &PgConnection) -> Result<(), Error> {
+    let is_deleted_duplication = select(exists(
+        member
+            .filter(team_id.eq(other_team_id))
+            .filter(user_id.eq(other_user_id))
+            .filter(deleted_at.is_not_null())
+    )).get_result::<bool>(connection)?;
+    match is_deleted_duplication {
+        true => Err(Error::DeletedDuplicationError),
+        false => Ok(()),
+    }
 }
 
 pub fn find_member_by_id(
@@ -80,24 +277,54 @@ pub fn find_member_by_id(
 ) -> Result<Member, Error> {
     member
         .find(other_member_id)
+        .filter(deleted_at.is_null())
         .get_result::<Member>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)
 }
 
+// The member is updated only if it still has the expected version, otherwise the conflict carries the current version.
 pub fn update_member(
     incoming_member: &UpdateMember,
     connection: &PgConnection,
 ) -> Result<Member, Error> {
-    diesel::update(member.find(&incoming_member.id))
-        .set((
-            name.eq(&incoming_member.name),
-            identity_num.eq(&incoming_member.identity_num),
-            role.eq(&incoming_member.role),
-            modification_date.eq(current_timestamp()),
-            expired_at.eq(&incoming_member.expired_at),
-        ))
-        .get_result::<Member>(connection)
-        .map_err(|e| Error::DBError(e))
+    retry_transaction(connection, || {
+        let updated_member = diesel::update(
+            member
+                .find(&incoming_member.id)
+                .filter(deleted_at.is_null())
+                .filter(version.eq(incoming_member.version))
+        )
+            .set((
+                name.eq(&incoming_member.name),
+                identity_num.eq(&incoming_member.identity_num),
+                role.eq(&incoming_member.role),
+                modification_date.eq(current_timestamp()),
+                expired_at.eq(&incoming_member.expired_at),
+                version.eq(version + 1),
+            ))
+            .get_result::<Member>(connection)
+            .optional()?;
+        match updated_member {
+            Some(updated_member) => Ok(updated_member),
+            None => Err(Error::VersionConflict(find_member_by_id(&incoming_member.id, connection)?.version)),
+        }
+    })
+}
+
+// The memberships of the user inside the active teams, joined through the member table.
+pub fn find_memberships_by_user_id(
+    other_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<Vec<Member>, Error> {
+    member
+        .inner_join(team_dsl::team)
+        .filter(user_id.eq(other_user_id))
+        .filter(deleted_at.is_null())
+        .filter(team_dsl::deleted_at.is_null())
+        .select(member_columns)
+        .order(member_id.asc())
+        .load::<Member>(connection)
+        .map_err(Error::DBError)
 }
 
 pub fn filter_members_by_name(
@@ -106,16 +333,19 @@ pub fn filter_members_by_name(
 ) -> Result<Vec<Member>, Error> {
     member
         .filter(name.eq(other_name))
+        .filter(deleted_at.is_null())
         .get_results::<Member>(connection)
-        .map_err(|e| Error::DBError(e))
+        .map_err(Error::DBError)
 }
 
 pub fn get_all_member_names_by_team_id(
     other_team_id: &Uuid,
     connection: &PgConnection,
 ) -> Result<Vec<Name>, Error> {
-    let query = format!("SELECT name FROM member WHERE team_id::text = '{}'", other_team_id);
-    diesel::sql_query(query)
+    member
+        .filter(team_id.eq(other_team_id))
+        .filter(deleted_at.is_null())
+        .select((name,))
         .load::<Name>(connection)
-        .map_err(|e| Error::DBError(e))
+        .map_err(Error::DBError)
 }
\ No newline at end of file
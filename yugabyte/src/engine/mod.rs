@@ -1,4 +1,54 @@
+pub mod authorization;
 pub mod auth_user;
+pub mod execution;
+pub(crate) mod filter;
 pub mod member;
+pub(crate) mod pagination;
+pub mod refresh_token;
+pub mod retry;
+pub mod search;
 pub mod team;
 pub mod user;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    const SQL_KEYWORDS: [&str; 5] = ["SELECT", "INSERT", "UPDATE", "DELETE", "WHERE"];
+
+    // Read every engine module, the queries must be built by diesel with bound parameters.
+    fn engine_sources() -> Vec<(String, String)> {
+        let engine_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/engine");
+        fs::read_dir(engine_dir)
+            .expect("Failed to read the engine directory")
+            .map(|entry| entry.expect("Failed to read the engine entry").path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "rs") && !path.ends_with("mod.rs"))
+            .map(|path| (path.display().to_string(), fs::read_to_string(&path).expect("Failed to read the engine file")))
+            .collect()
+    }
+
+    #[test]
+    fn engine_does_not_use_raw_sql_queries() {
+        for (path, source) in engine_sources() {
+            assert!(!source.contains("sql_query"), "{} uses diesel::sql_query", path);
+        }
+    }
+
+    #[test]
+    fn engine_does_not_interpolate_sql_strings() {
+        for (path, source) in engine_sources() {
+            for line in source.lines().filter(|line| line.contains("format!(")) {
+                let upper_line = line.to_uppercase();
+                assert!(
+                    !SQL_KEYWORDS.iter().any(|keyword| upper_line.contains(&format!("{} ", keyword))),
+                    "{} builds SQL with format!: {}", path, line.trim()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn engine_sources_are_found() {
+        assert!(engine_sources().iter().any(|(path, _)| path.ends_with("member.rs")));
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use error::error::Error;
+
+use crate::model::dto::{ListQueryDTO, Page, PaginationDTO};
+
+// The cursor is the position of the last record of the previous page: its id, and the values of the sorted columns of the
+// record when the list is sorted, the null values of the nullable columns are kept as None. The sort of the list is kept
+// with them like the sort of the query string, "-name" for a descending name, so they are not read in another order.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Cursor {
+    pub(crate) id: Uuid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sort: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sort_values: Vec<Option<String>>,
+}
+
+impl Cursor {
+    pub(crate) fn new(id: Uuid) -> Self {
+        Self { id, sort: Vec::new(), sort_values: Vec::new() }
+    }
+
+    pub(crate) fn sorted(id: Uuid, list_query_dto: &ListQueryDTO, sort_values: Vec<Option<String>>) -> Self {
+        Self { id, sort: sort_keys(list_query_dto), sort_values }
+    }
+}
+
+fn sort_keys(list_query_dto: &ListQueryDTO) -> Vec<String> {
+    list_query_dto
+        .sort
+        .iter()
+        .map(|sort_dto| match sort_dto.descending {
+            true => format!("-{}", sort_dto.field),
+            false => sort_dto.field.clone(),
+        })
+        .collect()
+}
+
+fn encode_cursor(cursor: &Cursor) -> String {
+    let serialized_cursor = serde_json::to_vec(cursor).expect("The cursor is always serializable");
+    base64::encode_config(serialized_cursor, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode_cursor(cursor: &str) -> Result<Cursor, Error> {
+    base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|decoded_cursor| serde_json::from_slice(&decoded_cursor).ok())
+        .ok_or_else(invalid_cursor)
+}
+
+pub(crate) fn invalid_cursor() -> Error {
+    Error::BadRequest("invalid-cursor".to_string())
+}
+
+// Check the page size and the offset, then decode the cursor of the request if it is given.
+pub(crate) fn page_start(pagination_dto: &PaginationDTO) -> Result<Option<Uuid>, Error> {
+    match sorted_page_start(pagination_dto, &ListQueryDTO::default())? {
+        Some(cursor) => Ok(Some(cursor.id)),
+        None => Ok(None),
+    }
+}
+
+// The cursor of a sorted list holds one value per sorted column, a cursor of another sort is rejected.
+pub(crate) fn sorted_page_start(pagination_dto: &PaginationDTO, list_query_dto: &ListQueryDTO) -> Result<Option<Cursor>, Error> {
+    pagination_dto.validate()?;
+    match pagination_dto.cursor.as_deref().map(decode_cursor).transpose()? {
+        Some(cursor) if cursor.sort != sort_keys(list_query_dto) => Err(Error::BadRequest("filter-format-error".to_string())),
+        Some(cursor) if cursor.sort_values.len() != cursor.sort.len() => Err(invalid_cursor()),
+        start => Ok(start),
+    }
+}
+
+// The lists load one more record than the page size to know if there is a next page.
+pub(crate) fn page_limit(pagination_dto: &PaginationDTO) -> i64 {
+    pagination_dto.page_size as i64 + 1
+}
+
+pub(crate) fn into_page<T, F: Fn(&T) -> &Uuid>(
+    records: Vec<T>,
+    pagination_dto: &PaginationDTO,
+    record_id: F,
+) -> Page<T> {
+    into_sorted_page(records, pagination_dto, |record| Cursor::new(*record_id(record)))
+}
+
+pub(crate) fn into_sorted_page<T, F: Fn(&T) -> Cursor>(
+    mut records: Vec<T>,
+    pagination_dto: &PaginationDTO,
+    record_cursor: F,
+) -> Page<T> {
+    let has_next_page = records.len() > pagination_dto.page_size as usize;
+    records.truncate(pagination_dto.page_size as usize);
+    let next_cursor = match has_next_page {
+        true => records.last().map(|last_record| encode_cursor(&record_cursor(last_record))),
+        false => None,
+    };
+    Page { items: records, next_cursor }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::dto::SortDTO;
+
+    use super::*;
+
+    fn pagination_dto(page_size: i32, cursor: Option<String>) -> PaginationDTO {
+        PaginationDTO { page_size, offset: 0, cursor }
+    }
+
+    #[test]
+    fn next_cursor_starts_the_next_page() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let page = into_page(ids.clone(), &pagination_dto(2, None), |id| id);
+        assert_eq!(page.items, ids[..2]);
+        let next_page = pagination_dto(2, page.next_cursor);
+        assert_eq!(page_start(&next_page).unwrap(), Some(ids[1]));
+
+        let last_page = into_page(ids[2..].to_vec(), &next_page, |id| id);
+        assert_eq!(last_page.next_cursor, None);
+    }
+
+    #[test]
+    fn invalid_pagination_is_rejected() {
+        assert!(matches!(page_start(&pagination_dto(0, None)), Err(Error::ValidationError(_))));
+        assert!(matches!(page_start(&pagination_dto(101, None)), Err(Error::ValidationError(_))));
+        assert!(matches!(page_start(&pagination_dto(10, Some("not-a-cursor".to_string()))), Err(Error::BadRequest(_))));
+    }
+
+    #[test]
+    fn the_sorted_cursor_keeps_the_sort_values() {
+        let list_query_dto = ListQueryDTO {
+            filters: vec![],
+            sort: vec![
+                SortDTO { field: "name".to_string(), descending: true },
+                SortDTO { field: "expired_at".to_string(), descending: false },
+            ],
+        };
+        let last_id = Uuid::new_v4();
+        let page = into_sorted_page(vec![1, 2], &pagination_dto(1, None), |_| {
+            Cursor::sorted(last_id, &list_query_dto, vec![Some("ali".to_string()), None])
+        });
+        let next_page = pagination_dto(1, page.next_cursor);
+        let cursor = sorted_page_start(&next_page, &list_query_dto).unwrap().unwrap();
+        assert_eq!(cursor.id, last_id);
+        assert_eq!(cursor.sort, vec!["-name".to_string(), "expired_at".to_string()]);
+        assert_eq!(cursor.sort_values, vec![Some("ali".to_string()), None]);
+
+        assert!(matches!(page_start(&next_page), Err(Error::BadRequest(_))));
+        assert!(matches!(sorted_page_start(&pagination_dto(1, Some(encode_cursor(&Cursor::new(last_id)))), &list_query_dto), Err(Error::BadRequest(_))));
+    }
+
+    #[test]
+    fn the_cursor_of_another_sort_is_rejected() {
+        let sorted_by = |descending: bool| ListQueryDTO {
+            filters: vec![],
+            sort: vec![SortDTO { field: "name".to_string(), descending }],
+        };
+        let cursor = encode_cursor(&Cursor::sorted(Uuid::new_v4(), &sorted_by(false), vec![Some("ali".to_string())]));
+        let next_page = pagination_dto(1, Some(cursor));
+        assert!(sorted_page_start(&next_page, &sorted_by(false)).unwrap().is_some());
+
+        let err = sorted_page_start(&next_page, &sorted_by(true)).unwrap_err();
+        assert!(matches!(err, Error::BadRequest(code) if code == "filter-format-error"));
+
+        let tampered_cursor = Cursor::sorted(Uuid::new_v4(), &sorted_by(false), vec![]);
+        let err = sorted_page_start(&pagination_dto(1, Some(encode_cursor(&tampered_cursor))), &sorted_by(false)).unwrap_err();
+        assert!(matches!(err, Error::BadRequest(code) if code == "invalid-cursor"));
+    }
+}
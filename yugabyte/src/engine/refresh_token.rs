@@ -0,0 +1,110 @@
+use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use uuid::Uuid;
+
+use error::error::Error;
+
+use crate::engine::retry::retry_transaction;
+use crate::model::refresh_token::RefreshToken;
+use crate::schema::refresh_token::dsl::{auth_user_id, refresh_token, revoked_at, token_hash};
+use crate::util::jwt::{generate_refresh_token, hash_refresh_token, REFRESH_TOKEN_TTL};
+use crate::util::utils::current_timestamp;
+
+// Persist a new refresh token for the auth_user and return the raw token that will be given to the client.
+pub fn issue_refresh_token(
+    other_auth_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<(String, RefreshToken), Error> {
+    let raw_refresh_token = generate_refresh_token();
+    let now = current_timestamp();
+    let initialized_refresh_token = RefreshToken {
+        id: Uuid::new_v4(),
+        auth_user_id: *other_auth_user_id,
+        token_hash: hash_refresh_token(&raw_refresh_token),
+        created_at: now,
+        expires_at: now + *REFRESH_TOKEN_TTL,
+        revoked_at: None,
+    };
+    retry_transaction(connection, || {
+        diesel::insert_into(refresh_token)
+            .values(&initialized_refresh_token)
+            .get_result::<RefreshToken>(connection)
+            .map_err(Error::from)
+    })
+        .map(|inserted_refresh_token| (raw_refresh_token, inserted_refresh_token))
+}
+
+pub fn find_refresh_token(
+    raw_refresh_token: &str,
+    connection: &PgConnection,
+) -> Result<RefreshToken, Error> {
+    refresh_token
+        .filter(token_hash.eq(hash_refresh_token(raw_refresh_token)))
+        .get_result::<RefreshToken>(connection)
+        .map_err(Error::from)
+}
+
+// Revoke the presented refresh token and issue a new one in the same transaction.
+// Returns None if the token is unknown or expired. Presenting an already revoked token means that it has been
+// leaked, so all the refresh tokens of its auth_user are revoked. The token is revoked only if it is still not revoked,
+// so of two concurrent rotations of the same token the one that revokes nothing is handled as a reuse too.
+pub fn rotate_refresh_token(
+    raw_refresh_token: &str,
+    connection: &PgConnection,
+) -> Result<Option<(String, RefreshToken)>, Error> {
+    retry_transaction(connection, || {
+        let found_refresh_token = match find_refresh_token(raw_refresh_token, connection) {
+            Ok(found_refresh_token) => found_refresh_token,
+            Err(Error::DBError(diesel::result::Error::NotFound)) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        if found_refresh_token.revoked_at.is_some() {
+            revoke_all_refresh_tokens(&found_refresh_token.auth_user_id, connection)?;
+            return Ok(None);
+        }
+        if found_refresh_token.expires_at <= current_timestamp() {
+            return Ok(None);
+        }
+
+        let revoked_refresh_tokens = diesel::update(refresh_token.find(&found_refresh_token.id).filter(revoked_at.is_null()))
+            .set(revoked_at.eq(current_timestamp()))
+            .execute(connection)?;
+        if revoked_refresh_tokens != 1 {
+            revoke_all_refresh_tokens(&found_refresh_token.auth_user_id, connection)?;
+            return Ok(None);
+        }
+        issue_refresh_token(&found_refresh_token.auth_user_id, connection).map(Some)
+    })
+}
+
+pub fn revoke_refresh_token(
+    raw_refresh_token: &str,
+    other_auth_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<bool, Error> {
+    retry_transaction(connection, || {
+        diesel::update(
+            refresh_token
+                .filter(token_hash.eq(hash_refresh_token(raw_refresh_token)))
+                .filter(auth_user_id.eq(other_auth_user_id))
+                .filter(revoked_at.is_null())
+        )
+            .set(revoked_at.eq(current_timestamp()))
+            .execute(connection)
+            .map(|updated_rows| updated_rows > 0)
+            .map_err(Error::from)
+    })
+}
+
+pub fn revoke_all_refresh_tokens(other_auth_user_id: &Uuid, connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        diesel::update(
+            refresh_token
+                .filter(auth_user_id.eq(other_auth_user_id))
+                .filter(revoked_at.is_null())
+        )
+            .set(revoked_at.eq(current_timestamp()))
+            .execute(connection)
+            .map_err(Error::from)
+    })
+}
@@ -0,0 +1,146 @@
+use std::thread;
+use std::time::Duration;
+
+use diesel::{Connection, PgConnection};
+use diesel::connection::TransactionManager;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use lazy_static::*;
+use log::warn;
+use rand::Rng;
+
+use error::error::Error;
+
+lazy_static! {
+    // The retry settings are read once from the environment (.env) like the other engine settings.
+    pub static ref RETRY_SETTINGS: RetrySettings = RetrySettings::from_env();
+}
+
+#[derive(Debug, Clone)]
+pub struct RetrySettings {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetrySettings {
+    fn from_env() -> Self {
+        dotenv::dotenv().ok();
+        Self {
+            max_retries: env_or_default("DB_TRANSACTION_MAX_RETRIES", 5) as u32,
+            base_delay: Duration::from_millis(env_or_default("DB_TRANSACTION_RETRY_BASE_DELAY_MS", 20)),
+            max_delay: Duration::from_millis(env_or_default("DB_TRANSACTION_RETRY_MAX_DELAY_MS", 1000)),
+        }
+    }
+
+    // Exponential backoff with full jitter, the delay of the retry is a random value up to base * 2^retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential_delay = self.base_delay
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        rand::thread_rng().gen_range(Duration::ZERO..=exponential_delay)
+    }
+}
+
+fn env_or_default(key: &str, default: u64) -> u64 {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(default)
+}
+
+// The distributed transactions of YugabyteDB conflict with each other and return 40001 or "Restart read required",
+// both of them are safe to retry from the beginning of the transaction.
+pub fn is_retryable(err: &Error) -> bool {
+    match err {
+        Error::DBError(DieselError::DatabaseError(DatabaseErrorKind::SerializationFailure, _)) => true,
+        Error::DBError(DieselError::DatabaseError(_, info)) => info.message().to_lowercase().contains("restart read required"),
+        _ => false,
+    }
+}
+
+// Run the write operation in a transaction and run it again when the transaction conflicts with another one.
+// The operation must be safe to repeat, it is called once per attempt. Inside an outer transaction, the conflict aborts the
+// outer transaction too, so the operation runs once and the outer transaction is the one retried.
+pub fn retry_transaction<T, F>(connection: &PgConnection, mut operation: F) -> Result<T, Error>
+    where F: FnMut() -> Result<T, Error> {
+    if TransactionManager::<PgConnection>::get_transaction_depth(connection.transaction_manager()) > 0 {
+        return connection.transaction::<_, Error, _>(&mut operation);
+    }
+
+    let settings = &*RETRY_SETTINGS;
+    let mut retry = 0;
+    loop {
+        match connection.transaction::<_, Error, _>(&mut operation) {
+            Err(err) if is_retryable(&err) => {
+                if retry >= settings.max_retries {
+                    warn!("The transaction conflicted {} times, giving up: {:?}", retry + 1, err);
+                    return Err(Error::ServiceUnavailable("transaction-retries-exhausted".to_string()));
+                }
+                let delay = settings.backoff(retry);
+                retry += 1;
+                warn!("The transaction conflicted, retry {} of {} after {:?}: {:?}", retry, settings.max_retries, delay, err);
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Info(&'static str);
+
+    impl diesel::result::DatabaseErrorInformation for Info {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn details(&self) -> Option<&str> {
+            None
+        }
+
+        fn hint(&self) -> Option<&str> {
+            None
+        }
+
+        fn table_name(&self) -> Option<&str> {
+            None
+        }
+
+        fn column_name(&self) -> Option<&str> {
+            None
+        }
+
+        fn constraint_name(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    fn database_error(kind: DatabaseErrorKind, message: &'static str) -> Error {
+        Error::DBError(DieselError::DatabaseError(kind, Box::new(Info(message))))
+    }
+
+    #[test]
+    fn serialization_and_restart_read_errors_are_retryable() {
+        assert!(is_retryable(&database_error(DatabaseErrorKind::SerializationFailure, "could not serialize access")));
+        assert!(is_retryable(&database_error(DatabaseErrorKind::__Unknown, "Restart read required at: read: ht=...")));
+        assert!(!is_retryable(&database_error(DatabaseErrorKind::UniqueViolation, "duplicate key")));
+        assert!(!is_retryable(&Error::DuplicationError));
+    }
+
+    #[test]
+    fn backoff_is_bounded() {
+        let settings = RetrySettings {
+            max_retries: 5,
+            base_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(100),
+        };
+        assert!(settings.backoff(0) <= Duration::from_millis(20));
+        assert!(settings.backoff(2) <= Duration::from_millis(80));
+        assert!(settings.backoff(10) <= Duration::from_millis(100));
+        assert!(settings.backoff(40) <= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+
+use diesel::{BoolExpressionMethods, ExpressionMethods, IntoSql, PgConnection, QueryDsl, RunQueryDsl, TextExpressionMethods};
+use diesel::pg::Pg;
+use diesel::sql_types::{Float, Integer, Text};
+use validator::Validate;
+
+use error::error::Error;
+
+use crate::model::member::Member;
+use crate::model::search::{MemberSearchResult, SearchDTO, SearchResult, TeamSearchResult, UserSearchResult};
+use crate::model::team::Team;
+use crate::model::user::User;
+use crate::schema::member;
+use crate::schema::member::dsl as member_dsl;
+use crate::schema::team;
+use crate::schema::team::dsl as team_dsl;
+use crate::schema::user;
+use crate::schema::user::dsl as user_dsl;
+
+#[derive(SqlType)]
+#[postgres(type_name = "tsvector")]
+pub struct TsVector;
+
+#[derive(SqlType)]
+#[postgres(type_name = "tsquery")]
+pub struct TsQuery;
+
+// The search functions are created by the add_search_indexes migration, the indexes are built on the same expressions.
+sql_function!(fn search_vector(document: Text) -> TsVector);
+sql_function!(fn search_vector_pair(first_document: Text, second_document: Text) -> TsVector);
+sql_function!(fn search_query(query: Text) -> TsQuery);
+sql_function!(fn search_headline(document: Text, query: TsQuery) -> Text);
+sql_function!(fn ts_rank(vector: TsVector, query: TsQuery, normalization: Integer) -> Float);
+sql_function!(fn similarity(first_document: Text, second_document: Text) -> Float);
+sql_function!(fn greatest(first_rank: Float, second_rank: Float) -> Float);
+
+// The full text rank is divided by itself plus one, so it is between 0 and 1 like the trigram similarity.
+const RANK_NORMALIZATION: i32 = 32;
+
+diesel_infix_operator!(Matches, " @@ ");
+// The trigram match of pg_trgm, it finds the misspelled words that the full text search misses.
+diesel_infix_operator!(TrigramSimilar, " % ");
+
+// Search the members, the teams and the users at once. Every result is ranked by its normalized full text rank plus the trigram
+// similarity of its best matching column, so the ranks of the three entities have the same scale and are merged by their rank.
+pub fn search(search_dto: &SearchDTO, connection: &PgConnection) -> Result<Vec<SearchResult>, Error> {
+    search_dto.validate()?;
+    let limit = search_dto.limit as i64;
+
+    let mut results = search_members(&search_dto.q, limit, connection)?;
+    results.extend(search_teams(&search_dto.q, limit, connection)?);
+    results.extend(search_users(&search_dto.q, limit, connection)?);
+    results.sort_by(|result, other_result| other_result.rank().partial_cmp(&result.rank()).unwrap_or(Ordering::Equal));
+    results.truncate(search_dto.limit as usize);
+    Ok(results)
+}
+
+fn search_members(q: &str, limit: i64, connection: &PgConnection) -> Result<Vec<SearchResult>, Error> {
+    member_search_query(q, limit)
+        .load::<(Member, f32, String)>(connection)
+        .map_err(Error::DBError)
+        .map(|found_members| found_members
+            .into_iter()
+            .map(|(found_member, rank, highlight)| SearchResult::Member(MemberSearchResult {
+                member: found_member,
+                rank: rank as f64,
+                highlight,
+            }))
+            .collect())
+}
+
+fn member_search_query(q: &str, limit: i64) -> member::BoxedQuery<'_, Pg, (member::SqlType, Float, Text)> {
+    let rank = ts_rank(search_vector(member_dsl::name), search_query(q), RANK_NORMALIZATION) + similarity(member_dsl::name, q);
+    member_dsl::member
+        .filter(member_dsl::deleted_at.is_null())
+        .filter(
+            Matches::new(search_vector(member_dsl::name), search_query(q))
+                .or(TrigramSimilar::new(member_dsl::name, q.into_sql::<Text>()))
+        )
+        .select((member::all_columns, rank, search_headline(member_dsl::name, search_query(q))))
+        .order(rank.desc())
+        .limit(limit)
+        .into_boxed()
+}
+
+fn search_teams(q: &str, limit: i64, connection: &PgConnection) -> Result<Vec<SearchResult>, Error> {
+    team_search_query(q, limit)
+        .load::<(Team, f32, String)>(connection)
+        .map_err(Error::DBError)
+        .map(|found_teams| found_teams
+            .into_iter()
+            .map(|(found_team, rank, highlight)| SearchResult::Team(TeamSearchResult {
+                team: found_team,
+                rank: rank as f64,
+                highlight,
+            }))
+            .collect())
+}
+
+fn team_search_query(q: &str, limit: i64) -> team::BoxedQuery<'_, Pg, (team::SqlType, Float, Text)> {
+    let document = search_vector_pair(team_dsl::name, team_dsl::description);
+    let rank = ts_rank(document, search_query(q), RANK_NORMALIZATION) + similarity(team_dsl::name, q);
+    team_dsl::team
+        .filter(team_dsl::deleted_at.is_null())
+        .filter(
+            Matches::new(document, search_query(q))
+                .or(TrigramSimilar::new(team_dsl::name, q.into_sql::<Text>()))
+        )
+        .select((team::all_columns, rank, search_headline(team_dsl::name.concat(": ").concat(team_dsl::description), search_query(q))))
+        .order(rank.desc())
+        .limit(limit)
+        .into_boxed()
+}
+
+fn search_users(q: &str, limit: i64, connection: &PgConnection) -> Result<Vec<SearchResult>, Error> {
+    user_search_query(q, limit)
+        .load::<(User, f32, String)>(connection)
+        .map_err(Error::DBError)
+        .map(|found_users| found_users
+            .into_iter()
+            .map(|(found_user, rank, highlight)| SearchResult::User(UserSearchResult {
+                user: found_user,
+                rank: rank as f64,
+                highlight,
+            }))
+            .collect())
+}
+
+// The user is matched by its name or its email, only the closest of them counts in the rank.
+fn user_search_query(q: &str, limit: i64) -> user::BoxedQuery<'_, Pg, (user::SqlType, Float, Text)> {
+    let document = search_vector_pair(user_dsl::name, user_dsl::email);
+    let rank = ts_rank(document, search_query(q), RANK_NORMALIZATION)
+        + greatest(similarity(user_dsl::name, q), similarity(user_dsl::email, q));
+    user_dsl::user
+        .filter(user_dsl::deleted_at.is_null())
+        .filter(
+            Matches::new(document, search_query(q))
+                .or(TrigramSimilar::new(user_dsl::name, q.into_sql::<Text>()))
+                .or(TrigramSimilar::new(user_dsl::email, q.into_sql::<Text>()))
+        )
+        .select((user::all_columns, rank, search_headline(user_dsl::name.concat(" ").concat(user_dsl::email), search_query(q))))
+        .order(rank.desc())
+        .limit(limit)
+        .into_boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::debug_query;
+
+    use super::*;
+
+    const Q: &str = "ali'; DROP TABLE member; --";
+
+    // The SQL of the query without the values of its bound parameters.
+    fn statement(sql: &str) -> &str {
+        sql.split(" -- binds: ").next().unwrap()
+    }
+
+    #[test]
+    fn the_search_query_is_a_bound_parameter() {
+        let sqls = [
+            debug_query::<Pg, _>(&member_search_query(Q, 20)).to_string(),
+            debug_query::<Pg, _>(&team_search_query(Q, 20)).to_string(),
+            debug_query::<Pg, _>(&user_search_query(Q, 20)).to_string(),
+        ];
+        for sql in &sqls {
+            assert!(!statement(sql).contains("DROP TABLE"), "{}", sql);
+            assert!(statement(sql).contains("search_query($"), "{}", sql);
+            assert!(statement(sql).contains(" % $"), "{}", sql);
+            assert!(sql.contains("\"ali'; DROP TABLE member; --\""), "{}", sql);
+        }
+        assert!(statement(&sqls[2]).contains("greatest(similarity("), "{}", sqls[2]);
+    }
+}
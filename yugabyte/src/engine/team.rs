@@ -1,69 +1,131 @@
-use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
+use chrono::NaiveDateTime;
+use diesel::{ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl};
+use diesel::dsl::{exists, select};
 use uuid::Uuid;
 
 use error::error::Error;
 
-use crate::model::dto::PaginationDTO;
-use crate::model::team::{NewTeam, Team};
-use crate::schema::team::dsl::{description, name, team};
+use crate::engine::member::{delete_members_by_team_id, insert_team_owners, restore_members_by_team_id};
+use crate::engine::filter::{filter_teams, sort_teams, team_cursor, teams_after};
+use crate::engine::pagination::{into_sorted_page, page_limit, sorted_page_start};
+use crate::engine::retry::retry_transaction;
+use crate::model::dto::{ListQueryDTO, Page, PaginationDTO};
+use crate::model::team::{NewTeam, Team, UpdateTeam};
+use crate::schema::team::dsl::{deleted_at, description, name, team, version};
 use crate::schema::team::dsl::id as team_id;
+use crate::util::jwt::Claims;
+use crate::util::utils::current_timestamp;
 
 impl NewTeam {
-    pub fn insert_team(&self, connection: &PgConnection) -> Result<Team, Error> {
+    // The team is inserted with its creator as the owner inside the same transaction.
+    pub fn insert_team(&self, claims: &Claims, connection: &PgConnection) -> Result<Team, Error> {
         let initialized_member = Team {
             id: Uuid::new_v4(),
             name: self.name.clone(), // I cloned the name only not the whole team object because the string is located in the heap memory.
-            description: self.description.clone() // I cloned the description only not the whole team object because the string is located in the heap memory.
+            description: self.description.clone(), // I cloned the description only not the whole team object because the string is located in the heap memory.
+            deleted_at: None,
+            version: 1,
         };
-        diesel::insert_into(team)
-            .values(initialized_member)
-            .get_result(connection)
-            .map_err(|_| Error::DuplicationError)
+        retry_transaction(connection, || {
+            check_deleted_duplication(&[&initialized_member.name], connection)?;
+            let inserted_team = diesel::insert_into(team)
+                .values(&initialized_member)
+                .get_result::<Team>(connection)?;
+            insert_team_owners(&[inserted_team.id], claims, connection)?;
+            Ok(inserted_team)
+        })
     }
 }
 
 pub fn insert_bulk_team(
     other_teams: &Vec<Team>,
+    claims: &Claims,
     connection: &PgConnection,
 ) -> Result<Vec<Team>, Error> {
-    diesel::insert_into(team)
-        .values(other_teams)
-        .get_results::<Team>(connection)
-        .map_err(|_| Error::DuplicationError)
+    retry_transaction(connection, || {
+        let other_team_ids: Vec<Uuid> = other_teams.iter().map(|other_team| other_team.id).collect();
+        let other_names: Vec<&String> = other_teams.iter().map(|other_team| &other_team.name).collect();
+        check_deleted_duplication(&other_names, connection)?;
+
+        let inserted_teams = diesel::insert_into(team)
+            .values(other_teams)
+            .get_results::<Team>(connection)?;
+        insert_team_owners(&other_team_ids, claims, connection)?;
+        Ok(inserted_teams)
+    })
+}
+
+// The name of a soft deleted team is kept for its restoration, so creating a team with it is rejected until the old team is
+// restored.
+fn check_deleted_duplication(other_names: &[&String], connection: &PgConnection) -> Result<(), Error> {
+    let is_deleted_duplication = select(exists(
+        team.filter(name.eq_any(other_names)).filter(deleted_at.is_not_null())
+    )).get_result::<bool>(connection)?;
+    match is_deleted_duplication {
+        true => Err(Error::DeletedDuplicationError),
+        false => Ok(()),
+    }
 }
 
 pub fn list_all_teams(
     pagination_dto: &PaginationDTO,
+    list_query_dto: &ListQueryDTO,
     connection: &PgConnection,
-) -> Result<Vec<Team>, Error> {
-    team
-        .limit(pagination_dto.page_size as i64)
-        .offset(pagination_dto.offset as i64)
+) -> Result<Page<Team>, Error> {
+    let mut query = sort_teams(filter_teams(list_query_dto)?, list_query_dto)?
+        .limit(page_limit(pagination_dto));
+    query = match sorted_page_start(pagination_dto, list_query_dto)? {
+        Some(cursor) => query.filter(teams_after(&cursor, list_query_dto)?),
+        None => query.offset(pagination_dto.offset as i64),
+    };
+    let teams = query
         .load::<Team>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)?;
+    Ok(into_sorted_page(teams, pagination_dto, |other_team| team_cursor(other_team, list_query_dto)))
 }
 
-pub fn count_teams(connection: &PgConnection) -> Result<i64, Error> {
-    team
+pub fn count_teams(list_query_dto: &ListQueryDTO, connection: &PgConnection) -> Result<i64, Error> {
+    filter_teams(list_query_dto)?
         .count()
         .get_result(connection)
-        .map_err(|e| Error::DBError(e))
+        .map_err(Error::DBError)
 }
 
-pub fn delete_team_by_id(other_team_id: &Uuid, connection: &PgConnection) -> bool {
-    match diesel::delete(team.filter(team_id.eq(other_team_id)))
-        .execute(connection)
-        .map_err(|e| Error::DBError(e)) {
-        Ok(0) => false,
-        Ok(1) => true,
-        _ => false,
-    }
+// The team is soft deleted with its members, they share the same deleted_at to be restored together.
+pub fn delete_team_by_id(other_team_id: &Uuid, connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        let deletion_date = current_timestamp();
+        let deleted_teams = diesel::update(team.filter(team_id.eq(other_team_id)).filter(deleted_at.is_null()))
+            .set(deleted_at.eq(deletion_date))
+            .execute(connection)
+            .map_err(Error::DBError)?;
+        if deleted_teams == 0 {
+            return Err(Error::NotFound("team-not-found".to_string()));
+        }
+        delete_members_by_team_id(other_team_id, &deletion_date, connection)?;
+        Ok(deleted_teams)
+    })
 }
 
-pub fn delete_all_teams(connection: &PgConnection) -> Result<usize, Error> {
-    diesel::delete(team)
-        .execute(connection)
-        .map_err(|err| Error::DBError(err))
+// Restore the soft deleted team with the members that were deleted with it, the members deleted before stay deleted.
+pub fn restore_team_by_id(
+    other_team_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<Team, Error> {
+    retry_transaction(connection, || {
+        let deleted_team = team
+            .filter(team_id.eq(other_team_id))
+            .filter(deleted_at.is_not_null())
+            .get_result::<Team>(connection)
+            .map_err(Error::DBError)?;
+        if let Some(deletion_date) = deleted_team.deleted_at {
+            restore_members_by_team_id(other_team_id, &deletion_date, connection)?;
+        }
+        diesel::update(team.find(other_team_id))
+            .set(deleted_at.eq(None::<NaiveDateTime>))
+            .get_result::<Team>(connection)
+            .map_err(Error::DBError)
+    })
 }
 
 pub fn find_team_by_id(
@@ -72,20 +134,45 @@ pub fn find_team_by_id(
 ) -> Result<Team, Error> {
     team
         .find(other_team_id)
+        .filter(deleted_at.is_null())
         .get_result::<Team>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)
 }
 
+// The active teams of the ids, the missing and the soft deleted ones are skipped.
+pub fn find_teams_by_ids(
+    other_team_ids: &[Uuid],
+    connection: &PgConnection,
+) -> Result<Vec<Team>, Error> {
+    team
+        .filter(team_id.eq_any(other_team_ids))
+        .filter(deleted_at.is_null())
+        .load::<Team>(connection)
+        .map_err(Error::DBError)
+}
 
-pub fn update_auth_user(
-    incoming_team: &Team,
+// The team is updated only if it still has the expected version, otherwise the conflict carries the current version.
+pub fn update_team(
+    incoming_team: &UpdateTeam,
     connection: &PgConnection,
 ) -> Result<Team, Error> {
-    diesel::update(team.find(&incoming_team.id))
-        .set((
-            name.eq(&incoming_team.name),
-            description.eq(&incoming_team.description),
-        ))
-        .get_result::<Team>(connection)
-        .map_err(|e| Error::DBError(e))
+    retry_transaction(connection, || {
+        let updated_team = diesel::update(
+            team
+                .find(&incoming_team.id)
+                .filter(deleted_at.is_null())
+                .filter(version.eq(incoming_team.version))
+        )
+            .set((
+                name.eq(&incoming_team.name),
+                description.eq(&incoming_team.description),
+                version.eq(version + 1),
+            ))
+            .get_result::<Team>(connection)
+            .optional()?;
+        match updated_team {
+            Some(updated_team) => Ok(updated_team),
+            None => Err(Error::VersionConflict(find_team_by_id(&incoming_team.id, connection)?.version)),
+        }
+    })
 }
@@ -1,52 +1,201 @@
-use diesel::{PgConnection, QueryDsl, RunQueryDsl};
+use std::slice;
+
+use chrono::NaiveDateTime;
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl};
+use diesel::dsl::{exists, select};
 use uuid::Uuid;
 
 use error::error::Error;
 
-use crate::model::dto::PaginationDTO;
-use crate::model::user::{NewUser, User};
-use crate::schema::user::dsl::user;
+use crate::engine::auth_user::{
+    delete_auth_user_by_id, delete_auth_users_by_ids, insert_bulk_auth_users, update_auth_user_email,
+};
+use crate::engine::member::{delete_members_by_user_id, restore_members_by_user_id};
+use crate::engine::filter::{filter_users, sort_users, user_cursor, users_after};
+use crate::engine::pagination::{into_sorted_page, page_limit, sorted_page_start};
+use crate::engine::retry::retry_transaction;
+use crate::model::auth_user::{AuthUser, AuthUserView};
+use crate::model::dto::{ListQueryDTO, Page, PaginationDTO, RegisteredUserDTO};
+use crate::model::user::{NewUser, UpdateUser, User};
+use crate::schema::user::dsl::{deleted_at, email, name, user};
+use crate::schema::user::dsl::id as user_id;
+use crate::util::utils::current_timestamp;
 
 impl NewUser {
     pub fn add_user(&self, connection: &PgConnection) -> Result<User, Error> {
+        self.add_user_with_id(&Uuid::new_v4(), connection)
+    }
+
+    pub fn add_user_with_id(&self, other_user_id: &Uuid, connection: &PgConnection) -> Result<User, Error> {
         let initialized_user = User {
-            id: Uuid::new_v4(),
+            id: *other_user_id,
             email: self.email.clone(),
             name: self.name.clone(),
+            deleted_at: None,
         };
-        diesel::insert_into(user)
-            .values(&initialized_user)
-            .get_result(connection)
-            .map_err(|_| Error::DuplicationError)
+        retry_transaction(connection, || {
+            check_deleted_duplication(slice::from_ref(&initialized_user), connection)?;
+            diesel::insert_into(user)
+                .values(&initialized_user)
+                .get_result(connection)
+                .map_err(Error::from)
+        })
+    }
+
+    // Create the user and its auth_user with the same id. They live in different databases, so the auth_user is inserted
+    // inside the transaction of the user: if the auth_user insertion fails, the user insertion is rolled back.
+    // If the commit of the user fails after the auth_user has been inserted, the auth_user will be deleted, before the
+    // retry of the conflicted transaction as well.
+    pub fn register_user(
+        &self,
+        core_connection: &PgConnection,
+        auth_connection: &PgConnection,
+    ) -> Result<RegisteredUserDTO, Error> {
+        let registered_id = Uuid::new_v4();
+        let mut auth_user_inserted = false;
+        let registration = retry_transaction(core_connection, || {
+            if auth_user_inserted {
+                if delete_auth_user_by_id(&registered_id, auth_connection).is_err() {
+                    return Err(Error::InternalServerError("compensating-delete-error".to_string()));
+                }
+                auth_user_inserted = false;
+            }
+            let inserted_user = self.add_user_with_id(&registered_id, core_connection)?;
+            let inserted_auth_user = self.add_auth_user_with_id(&registered_id, auth_connection)?;
+            auth_user_inserted = true;
+            Ok(RegisteredUserDTO {
+                user: inserted_user,
+                auth_user: AuthUserView::from(inserted_auth_user),
+            })
+        });
+        if registration.is_err() && auth_user_inserted && delete_auth_user_by_id(&registered_id, auth_connection).is_err() {
+            return Err(Error::InternalServerError("compensating-delete-error".to_string()));
+        }
+        registration
+    }
+}
+
+impl UpdateUser {
+    // The auth_user of the user is updated inside the transaction of the user when the email changes. Same as the registration,
+    // if the user is not committed after the email of the auth_user has been changed, its old email is put back.
+    pub fn update_user(
+        &self,
+        core_connection: &PgConnection,
+        auth_connection: &PgConnection,
+    ) -> Result<User, Error> {
+        let mut previous_email: Option<String> = None;
+        let update = retry_transaction(core_connection, || {
+            if let Some(old_email) = previous_email.take() {
+                update_auth_user_email(&self.id, &old_email, auth_connection)
+                    .map_err(|_| Error::InternalServerError("compensating-update-error".to_string()))?;
+            }
+            check_email_duplication(&self.id, &self.email, core_connection)?;
+            let current_user = find_user_by_id(&self.id, core_connection)?;
+            let updated_user = diesel::update(user.find(self.id))
+                .set((email.eq(&self.email), name.eq(&self.name)))
+                .get_result::<User>(core_connection)
+                .map_err(Error::from)?;
+            if current_user.email != self.email {
+                update_auth_user_email(&self.id, &self.email, auth_connection)?;
+                previous_email = Some(current_user.email);
+            }
+            Ok(updated_user)
+        });
+        if let (Err(_), Some(old_email)) = (&update, &previous_email) {
+            if update_auth_user_email(&self.id, old_email, auth_connection).is_err() {
+                return Err(Error::InternalServerError("compensating-update-error".to_string()));
+            }
+        }
+        update
     }
 }
 
+// Register a bulk of users with their auth_users, the auth_users are compensated the same way as register_user.
+pub fn register_bulk_users(
+    new_users: &[NewUser],
+    core_connection: &PgConnection,
+    auth_connection: &PgConnection,
+) -> Result<Vec<RegisteredUserDTO>, Error> {
+    let registered_users: Vec<User> = new_users
+        .iter()
+        .map(|new_user| User {
+            id: Uuid::new_v4(),
+            email: new_user.email.clone(),
+            name: new_user.name.clone(),
+            deleted_at: None,
+        })
+        .collect();
+    let registered_auth_users: Vec<AuthUser> = new_users
+        .iter()
+        .zip(&registered_users)
+        .map(|(new_user, registered_user)| AuthUser {
+            id: registered_user.id,
+            email: new_user.email.clone(),
+            password: new_user.password.clone(),
+        })
+        .collect();
+    let registered_ids: Vec<Uuid> = registered_users.iter().map(|registered_user| registered_user.id).collect();
+
+    let mut auth_users_inserted = false;
+    let registration = retry_transaction(core_connection, || {
+        if auth_users_inserted {
+            delete_auth_users_by_ids(&registered_ids, auth_connection)
+                .map_err(|_| Error::InternalServerError("compensating-delete-error".to_string()))?;
+            auth_users_inserted = false;
+        }
+        let inserted_users = insert_bulk_users(&registered_users, core_connection)?;
+        let inserted_auth_users = insert_bulk_auth_users(&registered_auth_users, auth_connection)?;
+        auth_users_inserted = true;
+        Ok(inserted_users
+            .into_iter()
+            .zip(inserted_auth_users)
+            .map(|(inserted_user, inserted_auth_user)| RegisteredUserDTO {
+                user: inserted_user,
+                auth_user: AuthUserView::from(inserted_auth_user),
+            })
+            .collect())
+    });
+    if registration.is_err() && auth_users_inserted && delete_auth_users_by_ids(&registered_ids, auth_connection).is_err() {
+        return Err(Error::InternalServerError("compensating-delete-error".to_string()));
+    }
+    registration
+}
+
 pub fn insert_bulk_users(
     other_users: &Vec<User>,
     connection: &PgConnection,
 ) -> Result<Vec<User>, Error> {
-    diesel::insert_into(user)
-        .values(other_users)
-        .get_results::<User>(connection)
-        .map_err(|_| Error::DuplicationError)
+    retry_transaction(connection, || {
+        check_deleted_duplication(other_users, connection)?;
+        diesel::insert_into(user)
+            .values(other_users)
+            .get_results::<User>(connection)
+            .map_err(Error::from)
+    })
 }
 
 pub fn list_all_users(
     pagination_dto: &PaginationDTO,
+    list_query_dto: &ListQueryDTO,
     connection: &PgConnection,
-) -> Result<Vec<User>, Error> {
-    user
-        .limit(pagination_dto.page_size as i64)
-        .offset(pagination_dto.offset as i64)
+) -> Result<Page<User>, Error> {
+    let mut query = sort_users(filter_users(list_query_dto)?, list_query_dto)?
+        .limit(page_limit(pagination_dto));
+    query = match sorted_page_start(pagination_dto, list_query_dto)? {
+        Some(cursor) => query.filter(users_after(&cursor, list_query_dto)?),
+        None => query.offset(pagination_dto.offset as i64),
+    };
+    let users = query
         .load::<User>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)?;
+    Ok(into_sorted_page(users, pagination_dto, |other_user| user_cursor(other_user, list_query_dto)))
 }
 
-pub fn count_users(connection: &PgConnection) -> Result<i64, Error> {
-    user
+pub fn count_users(list_query_dto: &ListQueryDTO, connection: &PgConnection) -> Result<i64, Error> {
+    filter_users(list_query_dto)?
         .count()
         .get_result(connection)
-        .map_err(|e| Error::DBError(e))
+        .map_err(Error::DBError)
 }
 
 pub fn find_user_by_id(
@@ -55,6 +204,86 @@ pub fn find_user_by_id(
 ) -> Result<User, Error> {
     user
         .find(other_user_id)
+        .filter(deleted_at.is_null())
         .get_result::<User>(connection)
-        .map_err(|err| Error::DBError(err))
+        .map_err(Error::DBError)
+}
+
+// The active users of the ids, the missing and the soft deleted ones are skipped.
+pub fn find_users_by_ids(
+    other_user_ids: &[Uuid],
+    connection: &PgConnection,
+) -> Result<Vec<User>, Error> {
+    user
+        .filter(user_id.eq_any(other_user_ids))
+        .filter(deleted_at.is_null())
+        .load::<User>(connection)
+        .map_err(Error::DBError)
+}
+
+// The user is soft deleted with its memberships, they share the same deleted_at to be restored together.
+pub fn delete_user_by_id(other_user_id: &Uuid, connection: &PgConnection) -> Result<usize, Error> {
+    retry_transaction(connection, || {
+        let deletion_date = current_timestamp();
+        let deleted_users = diesel::update(user.filter(user_id.eq(other_user_id)).filter(deleted_at.is_null()))
+            .set(deleted_at.eq(deletion_date))
+            .execute(connection)
+            .map_err(Error::DBError)?;
+        if deleted_users == 0 {
+            return Err(Error::NotFound("user-not-found".to_string()));
+        }
+        delete_members_by_user_id(other_user_id, &deletion_date, connection)?;
+        Ok(deleted_users)
+    })
+}
+
+// Restore the soft deleted user with the memberships that were deleted with it.
+pub fn restore_user_by_id(
+    other_user_id: &Uuid,
+    connection: &PgConnection,
+) -> Result<User, Error> {
+    retry_transaction(connection, || {
+        let deleted_user = user
+            .filter(user_id.eq(other_user_id))
+            .filter(deleted_at.is_not_null())
+            .get_result::<User>(connection)
+            .map_err(Error::DBError)?;
+        if let Some(deletion_date) = deleted_user.deleted_at {
+            restore_members_by_user_id(other_user_id, &deletion_date, connection)?;
+        }
+        diesel::update(user.find(other_user_id))
+            .set(deleted_at.eq(None::<NaiveDateTime>))
+            .get_result::<User>(connection)
+            .map_err(Error::DBError)
+    })
+}
+
+// The email stays unique between the soft deleted users, so creating it again is rejected until the old user is restored.
+fn check_deleted_duplication(other_users: &[User], connection: &PgConnection) -> Result<(), Error> {
+    let other_user_ids: Vec<Uuid> = other_users.iter().map(|other_user| other_user.id).collect();
+    let other_emails: Vec<&String> = other_users.iter().map(|other_user| &other_user.email).collect();
+    let is_deleted_duplication = select(exists(
+        user
+            .filter(user_id.eq_any(&other_user_ids).or(email.eq_any(&other_emails)))
+            .filter(deleted_at.is_not_null())
+    )).get_result::<bool>(connection)?;
+    match is_deleted_duplication {
+        true => Err(Error::DeletedDuplicationError),
+        false => Ok(()),
+    }
+}
+
+// The email is unique between all the users, the soft deleted ones included, so the other user holding it is looked up to tell
+// which of the duplications it is.
+fn check_email_duplication(other_user_id: &Uuid, other_email: &String, connection: &PgConnection) -> Result<(), Error> {
+    let duplicated_user = user
+        .filter(email.eq(other_email))
+        .filter(user_id.ne(other_user_id))
+        .get_result::<User>(connection)
+        .optional()?;
+    match duplicated_user {
+        Some(User { deleted_at: Some(_), .. }) => Err(Error::DeletedDuplicationError),
+        Some(_) => Err(Error::DuplicationError),
+        None => Ok(()),
+    }
 }
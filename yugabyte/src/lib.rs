@@ -1,5 +1,6 @@
 pub mod db_connection;
 pub mod engine;
+pub mod migration;
 pub mod model;
 pub mod schema;
 pub mod util;
@@ -8,6 +9,7 @@ pub mod context;
 
 #[macro_use]
 extern crate diesel;
+#[macro_use]
 extern crate diesel_migrations;
 
 extern crate juniper;
@@ -0,0 +1,123 @@
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use diesel::pg::PgConnection;
+use diesel::RunQueryDsl;
+use diesel::sql_types::{BigInt, Bool};
+use diesel_migrations::RunMigrationsError;
+
+use crate::db_connection::{AuthDBPool, CoreDBPool, current_setting, PgPool, set_config};
+
+mod core_migrations {
+    embed_migrations!("migrations");
+
+    pub(super) use self::embedded_migrations::run_with_output;
+}
+
+mod auth_migrations {
+    embed_migrations!("auth_migrations");
+
+    pub(super) use self::embedded_migrations::run_with_output;
+}
+
+// The key of the advisory lock that serializes the migrations of the instances starting together, the lock is per database.
+const MIGRATION_LOCK_KEY: i64 = 0x0072_7573_7477_6562;
+const MIGRATION_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const MIGRATION_LOCK_DEFAULT_TIMEOUT_SECONDS: u64 = 300;
+
+sql_function!(fn pg_try_advisory_lock(key: BigInt) -> Bool);
+sql_function!(fn pg_advisory_unlock(key: BigInt) -> Bool);
+
+// The migrations run at the startup only when RUN_MIGRATIONS is true, otherwise they are applied by the diesel CLI.
+pub fn run_migrations_if_enabled(core_pool: &CoreDBPool, auth_pool: &AuthDBPool) -> Result<(), String> {
+    dotenv::dotenv().ok();
+    let enabled = match std::env::var("RUN_MIGRATIONS") {
+        Ok(value) => value
+            .trim()
+            .parse::<bool>()
+            .map_err(|_| format!("RUN_MIGRATIONS has an invalid value {:?}, it must be true or false", value))?,
+        Err(_) => false,
+    };
+    if !enabled {
+        return Ok(());
+    }
+    let lock_timeout = match std::env::var("MIGRATION_LOCK_TIMEOUT_SECONDS") {
+        Ok(value) => value
+            .trim()
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .map_err(|_| format!("MIGRATION_LOCK_TIMEOUT_SECONDS has an invalid value {:?}, it must be a number of seconds", value))?,
+        Err(_) => Duration::from_secs(MIGRATION_LOCK_DEFAULT_TIMEOUT_SECONDS),
+    };
+    run_locked_migrations(&core_pool.0, "core", lock_timeout, |connection| {
+        core_migrations::run_with_output(connection, &mut io::stdout())
+    })?;
+    run_locked_migrations(&auth_pool.0, "auth", lock_timeout, |connection| {
+        auth_migrations::run_with_output(connection, &mut io::stdout())
+    })
+}
+
+// The advisory lock is held by the session, so the migrations must run on the same connection that took it. The migrations
+// can outlast the statement_timeout of the pool, so it is disabled while they run and set back before the connection is
+// returned to the pool.
+fn run_locked_migrations<F>(pool: &PgPool, database_name: &str, lock_timeout: Duration, migrations: F) -> Result<(), String>
+    where F: FnOnce(&PgConnection) -> Result<(), RunMigrationsError> {
+    let connection = pool
+        .get()
+        .map_err(|err| format!("Failed to get a connection to migrate the {} database: {}", database_name, err))?;
+    lock_migrations(&connection, database_name, lock_timeout)?;
+
+    let statement_timeout = diesel::select(current_setting("statement_timeout"))
+        .get_result::<String>(&connection)
+        .map_err(|err| format!("Failed to read the statement_timeout of the {} database: {}", database_name, err));
+    let result = statement_timeout
+        .clone()
+        .and_then(|_| set_statement_timeout(&connection, "0", database_name))
+        .and_then(|_| {
+            migrations(&connection)
+                .map_err(|err| format!("Failed to run the migrations of the {} database: {}", database_name, err))
+        });
+    let restored = match &statement_timeout {
+        Ok(statement_timeout) => set_statement_timeout(&connection, statement_timeout, database_name),
+        Err(_) => Ok(()),
+    };
+    let unlocked = diesel::select(pg_advisory_unlock(MIGRATION_LOCK_KEY))
+        .get_result::<bool>(&connection)
+        .map_err(|err| format!("Failed to unlock the migrations of the {} database: {}", database_name, err));
+    result.and(restored).and(unlocked.map(|_| ()))
+}
+
+// Wait for the other instance that is migrating until the timeout, its migrations are skipped as already applied once it
+// releases the lock. The advisory locks need PostgreSQL, or YugabyteDB 2025.1 or later with the ysql_yb_enable_advisory_locks
+// flag, the older YugabyteDB versions reject them.
+fn lock_migrations(connection: &PgConnection, database_name: &str, lock_timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + lock_timeout;
+    loop {
+        let locked = diesel::select(pg_try_advisory_lock(MIGRATION_LOCK_KEY))
+            .get_result::<bool>(connection)
+            .map_err(|err| format!(
+                "Failed to lock the migrations of the {} database: {}. The advisory locks need PostgreSQL or YugabyteDB 2025.1+ \
+                 with ysql_yb_enable_advisory_locks=true, otherwise set RUN_MIGRATIONS=false and apply the migrations with the diesel CLI",
+                database_name, err,
+            ))?;
+        if locked {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Timed out after {} seconds waiting for the migration lock of the {} database, another instance still holds it. \
+                 Restart once its migrations are applied or raise MIGRATION_LOCK_TIMEOUT_SECONDS",
+                lock_timeout.as_secs(), database_name,
+            ));
+        }
+        thread::sleep(MIGRATION_LOCK_RETRY_INTERVAL);
+    }
+}
+
+fn set_statement_timeout(connection: &PgConnection, statement_timeout: &str, database_name: &str) -> Result<(), String> {
+    diesel::select(set_config("statement_timeout", statement_timeout, false))
+        .execute(connection)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to set the statement_timeout of the {} database: {}", database_name, err))
+}
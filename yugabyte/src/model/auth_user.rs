@@ -8,7 +8,8 @@ use crate::schema::auth_user;
 
 /// I created this model separately for security purposes. and I prefer to put this table
 /// in another database to prevent knowing the password if the database has been hacked.
-#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Validate, GraphQLObject, Clone)]
+/// It is the storage model only and it is not serializable, use AuthUserView to return it.
+#[derive(Debug, Queryable, Insertable, Validate, Clone)]
 #[table_name = "auth_user"]
 pub struct AuthUser {
     pub id: Uuid,
@@ -16,4 +17,20 @@ pub struct AuthUser {
     pub email: String,
     #[validate(length(min = 9, max = 127, code = "password-length-error"))]
     pub password: String,
-}
\ No newline at end of file
+}
+
+/// The public read model of the AuthUser without the password hash.
+#[derive(Debug, Serialize, Deserialize, GraphQLObject, Clone)]
+pub struct AuthUserView {
+    pub id: Uuid,
+    pub email: String,
+}
+
+impl From<AuthUser> for AuthUserView {
+    fn from(auth_user: AuthUser) -> Self {
+        Self {
+            id: auth_user.id,
+            email: auth_user.email,
+        }
+    }
+}
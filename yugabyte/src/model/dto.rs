@@ -1,16 +1,83 @@
-use juniper::GraphQLInputObject;
+use juniper::{GraphQLEnum, GraphQLInputObject, GraphQLObject};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(Default, Deserialize, GraphQLInputObject, Debug)]
+use crate::model::auth_user::AuthUserView;
+use crate::model::role::Role;
+use crate::model::user::User;
+
+// The page starts after the cursor if it is given, otherwise after the offset. The offset queries get slower on the large
+// tables and skip or repeat the records inserted concurrently, so the cursor is preferred.
+#[derive(Default, Deserialize, GraphQLInputObject, Debug, Validate)]
 pub struct PaginationDTO {
+    #[validate(range(min = 1, max = 100, code = "page-size-range-error"))]
     pub page_size: i32,
+    #[serde(default)]
+    #[graphql(default = "0")]
+    #[validate(range(min = 0, code = "offset-range-error"))]
     pub offset: i32,
+    // The opaque next_cursor of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// The comparison of a list filter, written in the query string as `=`, `!=`, `>`, `>=`, `<`, `<=` and `~=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum)]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    // The case insensitive substring match of the text columns.
+    Contains,
+}
+
+#[derive(Debug, Clone, GraphQLInputObject)]
+pub struct FilterDTO {
+    pub field: String,
+    pub operator: FilterOperator,
+    // The null value matches the missing values, it is written as "null" in the query string.
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, GraphQLInputObject)]
+pub struct SortDTO {
+    pub field: String,
+    #[graphql(default = "false")]
+    pub descending: bool,
+}
+
+// The filters and the sort of a list, the fields are checked against the whitelist of the listed entity by the engine.
+// The REST endpoints read it from the query string like "role=admin&name~=ali&sort=-assigned_at,name".
+#[derive(Debug, Clone, Default, GraphQLInputObject)]
+pub struct ListQueryDTO {
+    #[graphql(default = "Vec::new()")]
+    pub filters: Vec<FilterDTO>,
+    #[graphql(default = "Vec::new()")]
+    pub sort: Vec<SortDTO>,
 }
 
 #[derive(Default, Serialize, Debug)]
 pub struct PaginatedResponseDTO<T> {
     pub paginated_list: Vec<T>,
     pub count: i64,
+    // The cursor of the next page, it is null after the last page.
+    pub next_cursor: Option<String>,
+}
+
+// The page returned by the list functions of the engine.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct AuthUserPage {
+    pub paginated_list: Vec<AuthUserView>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,7 +97,7 @@ pub struct MemberInfo {
     pub name: String,
     pub email: String,
     pub identity_num: String,
-    pub role: String,
+    pub role: Role,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -38,3 +105,28 @@ pub struct MemberName {
     pub name: String,
 }
 
+#[derive(Deserialize)]
+pub struct LoginDTO {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshTokenDTO {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokenPairDTO {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    // The number of seconds until the access token expires.
+    pub expires_in: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RegisteredUserDTO {
+    pub user: User,
+    pub auth_user: AuthUserView,
+}
@@ -1,14 +1,16 @@
 use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable};
-use diesel::types::VarChar;
 use juniper::GraphQLInputObject;
 use juniper::GraphQLObject;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
+use crate::model::role::Role;
 use crate::schema::member;
+use crate::util::utils::REGEX_FULL_WORD;
 
-#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Clone, GraphQLObject)]
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Clone)]
 #[table_name = "member"]
 pub struct Member {
     pub id: Uuid,
@@ -16,37 +18,45 @@ pub struct Member {
     pub user_id: Uuid,
     pub name: String,
     pub identity_num: String,
-    pub role: String,
+    pub role: Role,
     pub assigned_at: NaiveDateTime,
     pub expired_at: Option<NaiveDateTime>,
     pub modification_date: Option<NaiveDateTime>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
+    pub version: i32,
 }
 
-#[derive(GraphQLInputObject)]
+#[derive(Debug, Serialize, Deserialize, GraphQLInputObject, Validate)]
 pub struct UpdateMember {
     pub id: Uuid,
     pub team_id: Uuid,
     pub user_id: Uuid,
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
     pub name: String,
+    #[validate(length(min = 1, code = "identity-num-length-error"))]
     pub identity_num: String,
-    pub role: String,
+    pub role: Role,
     pub assigned_at: NaiveDateTime,
     pub expired_at: Option<NaiveDateTime>,
     pub modification_date: Option<NaiveDateTime>,
+    // The version of the member read by the client, the update fails with a conflict if the member has changed since.
+    pub version: i32,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, GraphQLInputObject)]
+#[derive(Default, Debug, Serialize, Deserialize, GraphQLInputObject, Validate)]
 pub struct NewMember {
     pub team_id: Uuid,
     pub user_id: Uuid,
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
     pub name: String,
+    #[validate(length(min = 1, code = "identity-num-length-error"))]
     pub identity_num: String,
-    pub role: String,
+    pub role: Role,
     pub expired_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, QueryableByName, GraphQLObject, Serialize, Deserialize)]
+#[derive(Debug, Queryable, GraphQLObject, Serialize, Deserialize)]
 pub struct Name {
-    #[sql_type = "VarChar"]
     pub name: String,
 }
\ No newline at end of file
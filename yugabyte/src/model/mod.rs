@@ -1,5 +1,8 @@
 pub mod auth_user;
 pub mod member;
 pub mod dto;
+pub mod refresh_token;
+pub mod role;
+pub mod search;
 pub mod team;
 pub mod user;
@@ -0,0 +1,17 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use uuid::Uuid;
+
+use crate::schema::refresh_token;
+
+/// The raw refresh token is never stored, only its hash, so this model is not serializable on purpose.
+#[derive(Debug, Queryable, Insertable, Clone)]
+#[table_name = "refresh_token"]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub auth_user_id: Uuid,
+    pub token_hash: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,113 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::Pg;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::VarChar;
+use juniper::GraphQLEnum;
+use serde::{Deserialize, Serialize};
+
+/// The role of a member inside its team, it is stored in the `member.role` column as a lowercase string.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, GraphQLEnum)]
+#[sql_type = "VarChar"]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Owner,
+    Admin,
+    #[default]
+    Member,
+    Viewer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ViewMembers,
+    ManageMembers,
+    ManageTeam,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Member => "member",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    // The permission matrix of the team roles.
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::ViewMembers => true,
+            Permission::ManageMembers => matches!(self, Role::Owner | Role::Admin),
+            Permission::ManageTeam => matches!(self, Role::Owner),
+        }
+    }
+
+    // Only the owner can add, change or remove another owner, the admin can manage the rest of the roles.
+    pub fn can_manage(&self, other_role: &Role) -> bool {
+        match other_role {
+            Role::Owner => self.has_permission(Permission::ManageTeam),
+            _ => self.has_permission(Permission::ManageMembers),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "owner" => Ok(Role::Owner),
+            "admin" => Ok(Role::Admin),
+            "member" => Ok(Role::Member),
+            "viewer" => Ok(Role::Viewer),
+            _ => Err(format!("Unrecognized role: {}", role)),
+        }
+    }
+}
+
+impl ToSql<VarChar, Pg> for Role {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<VarChar, Pg> for Role {
+    fn from_sql(bytes: Option<&[u8]>) -> deserialize::Result<Self> {
+        let role = <String as FromSql<VarChar, Pg>>::from_sql(bytes)?;
+        Role::from_str(&role).map_err(|err| err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permission_matrix() {
+        assert!(Role::Owner.has_permission(Permission::ManageTeam));
+        assert!(Role::Admin.has_permission(Permission::ManageMembers));
+        assert!(!Role::Admin.has_permission(Permission::ManageTeam));
+        assert!(!Role::Member.has_permission(Permission::ManageMembers));
+        assert!(Role::Viewer.has_permission(Permission::ViewMembers));
+        assert!(!Role::Viewer.has_permission(Permission::ManageMembers));
+    }
+
+    #[test]
+    fn only_owner_can_manage_owner() {
+        assert!(Role::Owner.can_manage(&Role::Owner));
+        assert!(!Role::Admin.can_manage(&Role::Owner));
+        assert!(Role::Admin.can_manage(&Role::Admin));
+        assert!(!Role::Member.can_manage(&Role::Viewer));
+    }
+
+    #[test]
+    fn role_from_str() {
+        assert_eq!(Role::from_str("admin"), Ok(Role::Admin));
+        assert!(Role::from_str("Admin").is_err());
+    }
+}
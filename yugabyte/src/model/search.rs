@@ -0,0 +1,62 @@
+use juniper::GraphQLInputObject;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::model::member::Member;
+use crate::model::team::Team;
+use crate::model::user::User;
+
+#[derive(Debug, Deserialize, GraphQLInputObject, Validate)]
+pub struct SearchDTO {
+    // The words to find, written like a web search: "ali -said" or "\"team lead\"".
+    #[validate(length(min = 1, max = 256, code = "search-query-length-error"))]
+    pub q: String,
+    #[serde(default = "default_search_limit")]
+    #[graphql(default = "20")]
+    #[validate(range(min = 1, max = 100, code = "search-limit-range-error"))]
+    pub limit: i32,
+}
+
+fn default_search_limit() -> i32 {
+    20
+}
+
+// The highlight is the searched text HTML escaped, with the matched words wrapped in <mark> tags.
+#[derive(Debug, Serialize)]
+pub struct MemberSearchResult {
+    pub member: Member,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeamSearchResult {
+    pub team: Team,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserSearchResult {
+    pub user: User,
+    pub rank: f64,
+    pub highlight: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SearchResult {
+    Member(MemberSearchResult),
+    Team(TeamSearchResult),
+    User(UserSearchResult),
+}
+
+impl SearchResult {
+    pub fn rank(&self) -> f64 {
+        match self {
+            SearchResult::Member(result) => result.rank,
+            SearchResult::Team(result) => result.rank,
+            SearchResult::User(result) => result.rank,
+        }
+    }
+}
@@ -1,20 +1,79 @@
+use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable};
-use juniper::GraphQLObject;
+use juniper::GraphQLInputObject;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::schema::team;
+use crate::util::utils::REGEX_FULL_WORD;
 
-#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Clone, GraphQLObject)]
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Clone)]
 #[table_name = "team"]
 pub struct Team {
     pub id: Uuid,
     pub name: String,
     pub description: String,
+    // Set when the record is soft deleted, the clients cannot set it, it is cleared by the restore endpoints only.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
+    pub version: i32,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Serialize, Deserialize, GraphQLInputObject, Validate)]
 pub struct NewTeam {
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
     pub name: String,
     pub description: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, GraphQLInputObject, Validate)]
+pub struct UpdateTeam {
+    pub id: Uuid,
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
+    pub name: String,
+    pub description: String,
+    // The version of the team read by the client, the update fails with a conflict if the team has changed since.
+    pub version: i32,
+}
+
+// The team sent to PUT /team/{team_id}, it replaces all the fields of the team.
+#[derive(Debug, Deserialize, Validate)]
+pub struct TeamReplacement {
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
+    pub name: String,
+    pub description: String,
+    pub version: i32,
+}
+
+impl TeamReplacement {
+    pub fn into_update(self, team_id: Uuid) -> UpdateTeam {
+        UpdateTeam {
+            id: team_id,
+            name: self.name,
+            description: self.description,
+            version: self.version,
+        }
+    }
+}
+
+// The changes of the team sent to PATCH /team/{team_id}, the omitted fields keep their current values.
+#[derive(Debug, Deserialize, Validate)]
+pub struct TeamChanges {
+    #[validate(length(min = 1, code = "name-length-error"), regex(path = "REGEX_FULL_WORD", code = "name-format-error"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub version: i32,
+}
+
+impl TeamChanges {
+    // Apply the changes on the current team, the version is kept as the expected version of the update.
+    pub fn into_update(self, current_team: Team) -> UpdateTeam {
+        UpdateTeam {
+            id: current_team.id,
+            name: self.name.unwrap_or(current_team.name),
+            description: self.description.unwrap_or(current_team.description),
+            version: self.version,
+        }
+    }
+}
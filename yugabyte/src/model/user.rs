@@ -1,25 +1,39 @@
+use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable};
 use juniper::GraphQLInputObject;
-use juniper::GraphQLObject;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::schema::user;
 
-#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Validate, Clone, GraphQLObject)]
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Validate, Clone)]
 #[table_name = "user"]
 pub struct User {
     pub id: Uuid,
     #[validate(email(code = "email-format-error"))]
     pub email: String,
     pub name: String,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, GraphQLInputObject, Validate)]
 pub struct NewUser {
     #[validate(email(code = "email-format-error"))]
     pub email: String,
+    #[validate(length(min = 1, code = "name-length-error"))]
     pub name: String,
+    #[validate(length(min = 9, max = 127, code = "password-length-error"))]
     pub password: String,
-}
\ No newline at end of file
+}
+
+// The email of the user is the login of its auth_user as well, so both of them are changed together.
+#[derive(Debug, Deserialize, GraphQLInputObject, Validate)]
+pub struct UpdateUser {
+    pub id: Uuid,
+    #[validate(email(code = "email-format-error"))]
+    pub email: String,
+    #[validate(length(min = 1, code = "name-length-error"))]
+    pub name: String,
+}
@@ -1,3 +1,4 @@
+// The auth_user and refresh_token tables live in the auth database (auth_migrations), the rest in the core database.
 table! {
     auth_user (id) {
         id -> Uuid,
@@ -17,6 +18,19 @@ table! {
         assigned_at -> Timestamp,
         expired_at -> Nullable<Timestamp>,
         modification_date -> Nullable<Timestamp>,
+        deleted_at -> Nullable<Timestamp>,
+        version -> Int4,
+    }
+}
+
+table! {
+    refresh_token (id) {
+        id -> Uuid,
+        auth_user_id -> Uuid,
+        token_hash -> Varchar,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
     }
 }
 
@@ -25,6 +39,8 @@ table! {
         id -> Uuid,
         name -> Varchar,
         description -> Varchar,
+        deleted_at -> Nullable<Timestamp>,
+        version -> Int4,
     }
 }
 
@@ -33,15 +49,18 @@ table! {
         id -> Uuid,
         email -> Varchar,
         name -> Varchar,
+        deleted_at -> Nullable<Timestamp>,
     }
 }
 
 joinable!(member -> team (team_id));
 joinable!(member -> user (user_id));
+joinable!(refresh_token -> auth_user (auth_user_id));
 
 allow_tables_to_appear_in_same_query!(
     auth_user,
     member,
+    refresh_token,
     team,
     user,
 );
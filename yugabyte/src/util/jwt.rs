@@ -0,0 +1,114 @@
+use chrono::Duration;
+use jsonwebtoken::{Algorithm, decode, DecodingKey, encode, EncodingKey, Header, Validation};
+use lazy_static::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use error::error::Error;
+
+use crate::model::auth_user::AuthUser;
+
+// The secret is not shipped with a default value, it is checked at the startup by check_jwt_secret.
+const JWT_SECRET_MIN_LENGTH: usize = 32;
+
+lazy_static! {
+    static ref JWT_SECRET: Result<String, String> = jwt_secret_from_env();
+    pub static ref ACCESS_TOKEN_TTL: Duration = Duration::seconds(env_or_default("JWT_ACCESS_TOKEN_TTL_SECONDS", 15 * 60));
+    pub static ref REFRESH_TOKEN_TTL: Duration = Duration::seconds(env_or_default("JWT_REFRESH_TOKEN_TTL_SECONDS", 14 * 24 * 60 * 60));
+}
+
+fn jwt_secret_from_env() -> Result<String, String> {
+    dotenv::dotenv().ok();
+    match std::env::var("JWT_SECRET").ok().filter(|secret| !secret.is_empty()) {
+        Some(secret) if secret.len() >= JWT_SECRET_MIN_LENGTH => Ok(secret),
+        Some(_) => Err(format!("JWT_SECRET must be at least {} characters long", JWT_SECRET_MIN_LENGTH)),
+        None => Err("JWT_SECRET must be set".to_string()),
+    }
+}
+
+// Stop the startup when the secret is missing or too short, instead of failing the first request that signs a token.
+pub fn check_jwt_secret() -> Result<(), String> {
+    JWT_SECRET.as_ref().map(|_| ()).map_err(Clone::clone)
+}
+
+fn jwt_secret() -> Result<&'static [u8], Error> {
+    JWT_SECRET
+        .as_ref()
+        .map(|secret| secret.as_bytes())
+        .map_err(|_| Error::InternalServerError("jwt-secret-error".to_string()))
+}
+
+fn env_or_default(key: &str, default: i64) -> i64 {
+    dotenv::dotenv().ok();
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Claims {
+    // The id of the authenticated AuthUser.
+    pub sub: Uuid,
+    pub email: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+// Issue a short-lived HS256 signed access token for the given auth_user.
+pub fn encode_access_token(authenticated_user: &AuthUser) -> Result<String, Error> {
+    let now = chrono::Utc::now();
+    let claims = Claims {
+        sub: authenticated_user.id,
+        email: authenticated_user.email.clone(),
+        iat: now.timestamp(),
+        exp: (now + *ACCESS_TOKEN_TTL).timestamp(),
+    };
+    encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(jwt_secret()?))
+        .map_err(|_| Error::InternalServerError("token-encoding-error".to_string()))
+}
+
+// Decode the access token and validate its signature and expiration date.
+pub fn decode_access_token(token: &str) -> Result<Claims, Error> {
+    decode::<Claims>(token, &DecodingKey::from_secret(jwt_secret()?), &Validation::new(Algorithm::HS256))
+        .map(|token_data| token_data.claims)
+        .map_err(|_| Error::Unauthorized("invalid-access-token".to_string()))
+}
+
+// The refresh token is an opaque random string, only its hash is persisted.
+pub fn generate_refresh_token() -> String {
+    format!("{}{}", Uuid::new_v4().to_simple(), Uuid::new_v4().to_simple())
+}
+
+pub fn hash_refresh_token(raw_refresh_token: &str) -> String {
+    format!("{:x}", Sha256::digest(raw_refresh_token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_access_token() {
+        std::env::set_var("JWT_SECRET", "a-test-secret-that-is-long-enough-to-sign");
+        let authenticated_user = AuthUser {
+            id: Uuid::new_v4(),
+            email: "abdelaziz@gmail.com".to_string(),
+            password: "hashed".to_string(),
+        };
+        let token = encode_access_token(&authenticated_user).unwrap();
+        let claims = decode_access_token(&token).unwrap();
+        assert_eq!(claims.sub, authenticated_user.id);
+        assert_eq!(claims.email, authenticated_user.email);
+        assert!(decode_access_token(&format!("{}x", token)).is_err());
+    }
+
+    #[test]
+    fn hash_refresh_token_is_stable() {
+        let raw_refresh_token = generate_refresh_token();
+        assert_eq!(raw_refresh_token.len(), 64);
+        assert_eq!(hash_refresh_token(&raw_refresh_token), hash_refresh_token(&raw_refresh_token));
+        assert_ne!(hash_refresh_token(&raw_refresh_token), raw_refresh_token);
+    }
+}
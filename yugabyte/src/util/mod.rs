@@ -1 +1,3 @@
+pub mod jwt;
+pub mod password;
 pub mod utils;
\ No newline at end of file
@@ -0,0 +1,120 @@
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use lazy_static::*;
+
+use error::error::Error;
+
+lazy_static! {
+    // The cost parameters are read once from the environment (.env) and fall back to the OWASP recommended values.
+    pub static ref ARGON2_PARAMS: Params = argon2_params_from_env();
+    // The hash verified when there is no stored hash, so an unknown email takes as long to reject as a wrong password.
+    static ref DUMMY_PASSWORD_HASH: String = hash_password("dummy-password").expect("Failed to hash the dummy password");
+}
+
+fn env_or_default(key: &str, default: u32) -> u32 {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(default)
+}
+
+fn argon2_params_from_env() -> Params {
+    dotenv::dotenv().ok();
+    Params::new(
+        env_or_default("ARGON2_MEMORY_COST", Params::DEFAULT_M_COST),
+        env_or_default("ARGON2_TIME_COST", Params::DEFAULT_T_COST),
+        env_or_default("ARGON2_PARALLELISM", Params::DEFAULT_P_COST),
+        None,
+    )
+        .expect("Invalid Argon2 cost parameters")
+}
+
+fn argon2_hasher(params: &Params) -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+}
+
+// Hash the plain password with Argon2id and a random per-user salt, the result is a PHC string.
+pub fn hash_password(plain_password: &str) -> Result<String, Error> {
+    hash_password_with(plain_password, &ARGON2_PARAMS)
+}
+
+pub fn hash_password_with(plain_password: &str, params: &Params) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    argon2_hasher(params)
+        .hash_password(plain_password.as_bytes(), &salt)
+        .map(|password_hash| password_hash.to_string())
+        .map_err(|_| Error::InternalServerError("password-hash-error".to_string()))
+}
+
+// Verify the candidate password against the stored PHC string, the cost parameters are taken from the stored hash.
+// A stored password that is not a PHC string never matches, the dummy hash is verified instead to take the same time.
+pub fn verify_password(stored_hash: &str, candidate: &str) -> bool {
+    match PasswordHash::new(stored_hash) {
+        Ok(parsed_hash) => Argon2::default().verify_password(candidate.as_bytes(), &parsed_hash).is_ok(),
+        Err(_) => {
+            verify_dummy_password(candidate);
+            false
+        }
+    }
+}
+
+// Spend the time of a password verification for the unknown users, the login fails anyway.
+pub fn verify_dummy_password(candidate: &str) {
+    verify_password(&DUMMY_PASSWORD_HASH, candidate);
+}
+
+// The stored hash needs to be recomputed when it was produced with another algorithm, version or cost parameters.
+pub fn needs_rehash(stored_hash: &str) -> bool {
+    needs_rehash_with(stored_hash, &ARGON2_PARAMS)
+}
+
+pub fn needs_rehash_with(stored_hash: &str, params: &Params) -> bool {
+    match PasswordHash::new(stored_hash) {
+        Ok(parsed_hash) => {
+            if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+                || parsed_hash.version != Some(Version::V0x13.into()) {
+                return true;
+            }
+            match Params::try_from(&parsed_hash) {
+                Ok(stored_params) => stored_params.m_cost() != params.m_cost()
+                    || stored_params.t_cost() != params.t_cost()
+                    || stored_params.p_cost() != params.p_cost(),
+                Err(_) => true,
+            }
+        }
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cheap_params() -> Params {
+        Params::new(1024, 1, 1, None).unwrap()
+    }
+
+    #[test]
+    fn hash_password_produces_argon2id_phc_string() {
+        let hashed = hash_password_with("123456789", &cheap_params()).unwrap();
+        assert!(hashed.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert_ne!(hashed, hash_password_with("123456789", &cheap_params()).unwrap());
+    }
+
+    #[test]
+    fn verify_password_succ_and_failed() {
+        let hashed = hash_password_with("123456789", &cheap_params()).unwrap();
+        assert!(verify_password(&hashed, "123456789"));
+        assert!(!verify_password(&hashed, "987654321"));
+        assert!(!verify_password("plain-text", "plain-text"));
+    }
+
+    #[test]
+    fn needs_rehash_when_params_changed() {
+        let hashed = hash_password_with("123456789", &cheap_params()).unwrap();
+        assert!(!needs_rehash_with(&hashed, &cheap_params()));
+        assert!(needs_rehash_with(&hashed, &Params::new(2048, 2, 1, None).unwrap()));
+        assert!(needs_rehash_with("plain-text", &cheap_params()));
+    }
+}